@@ -0,0 +1,205 @@
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use sdl3::event::Event;
+
+use crate::Driad;
+use crate::font::PutGlyphError;
+use crate::mouse::MouseEvent;
+
+/// A game driven by [`Driad::run`]. Every hook has a default that does
+/// nothing, returning [`ControlFlow::Break`] from a hook ends the loop.
+pub trait App {
+    /// Called for every event driad itself didn't consume, before the updates
+    /// of the frame. The loop ends after an [`Event::Quit`] regardless of what
+    /// this returns.
+    fn handle_event(&mut self, _driad : &mut Driad, _event : &Event) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called for every mouse event after [`Self::handle_event`], with its
+    /// position translated into the cells of every layer.
+    fn handle_mouse(&mut self, _driad : &mut Driad, _event : &MouseEvent) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called at a steady [`LoopSettings::tick_rate`], however long frames
+    /// take. `step` is the time covered by a single tick.
+    fn fixed_update(&mut self, _driad : &mut Driad, _step : Duration) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called once per frame after the fixed updates, with the time since the
+    /// last frame.
+    fn update(&mut self, _driad : &mut Driad, _dt : Duration) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called once per frame to fill the layers and submit draw passes.
+    /// `alpha` is how far into the next tick the frame is, from `0.0` to
+    /// `1.0`, for interpolating between fixed updates.
+    fn draw(&mut self, _driad : &mut Driad, _alpha : f64) {}
+}
+
+/// Timing of the loop run by [`Driad::run`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoopSettings {
+    /// Fixed updates per second.
+    pub tick_rate :      u32,
+    /// Frames are slept out to stay at or below this rate. [`Driad::new`]
+    /// leaves this `None` when the window has vsync, which already paces the
+    /// loop.
+    pub max_fps :        Option<u32>,
+    /// The most fixed updates run in a single frame. After a long stall the
+    /// ticks beyond this are dropped instead of being caught up on, so a slow
+    /// frame can't snowball into slower ones.
+    pub max_ticks :      u32,
+    /// How many frames [`FrameTimes`] averages over.
+    pub average_frames : usize,
+}
+
+impl Default for LoopSettings {
+    fn default() -> Self {
+        Self {
+            tick_rate :      60,
+            max_fps :        Some(60),
+            max_ticks :      5,
+            average_frames : 60,
+        }
+    }
+}
+
+impl LoopSettings {
+    /// The time covered by a single fixed update.
+    #[must_use]
+    pub fn step(&self) -> Duration {
+        Duration::from_secs(1) / self.tick_rate.max(1)
+    }
+}
+
+/// Measurements of recent frames.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FrameTimes {
+    recent :     VecDeque<Duration>,
+    /// Fixed updates run during the last frame.
+    pub ticks :  u32,
+    /// Frames since the loop started.
+    pub frames : u64,
+}
+
+impl FrameTimes {
+    /// Records the length of a frame, keeping the last `keep` of them.
+    pub fn push(&mut self, frame : Duration, keep : usize) {
+        self.recent.push_back(frame);
+        while self.recent.len() > keep.max(1) {
+            self.recent.pop_front();
+        }
+        self.frames += 1;
+    }
+
+    /// The length of the last frame.
+    #[must_use]
+    pub fn last(&self) -> Duration {
+        self.recent.back().copied().unwrap_or_default()
+    }
+
+    /// The average length of the recent frames.
+    #[must_use]
+    pub fn average(&self) -> Duration {
+        self.recent
+            .iter()
+            .sum::<Duration>()
+            .checked_div(u32::try_from(self.recent.len()).unwrap_or(u32::MAX))
+            .unwrap_or_default()
+    }
+
+    /// Frames per second based on [`Self::average`].
+    #[must_use]
+    pub fn fps(&self) -> f64 {
+        let average = self.average().as_secs_f64();
+        if average > 0.0 { 1.0 / average } else { 0.0 }
+    }
+}
+
+impl Driad {
+    /// Runs `app` until it or the window asks to quit, handling events,
+    /// updates, drawing and presenting each frame.
+    ///
+    /// Events go to [`Self::handle_event`] first and only reach the app if
+    /// driad didn't consume them. Plugin draws are submitted after
+    /// [`App::draw`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::render`].
+    pub fn run(&mut self, app : &mut impl App) -> Result<(), PutGlyphError> {
+        let mut last_frame = Instant::now();
+        let mut accumulator = Duration::ZERO;
+
+        loop {
+            let frame_start = Instant::now();
+            let dt = frame_start - last_frame;
+            last_frame = frame_start;
+
+            let settings = self.loop_settings;
+            self.frame_times.push(dt, settings.average_frames);
+
+            let events : Vec<_> = self.event_pump.poll_iter().collect();
+            for event in events {
+                if self.handle_event(&event) {
+                    continue;
+                }
+
+                if app.handle_event(self, &event).is_break() || matches!(event, Event::Quit { .. })
+                {
+                    return Ok(());
+                }
+
+                if let Some(mouse) = self.mouse_event(&event)
+                    && app.handle_mouse(self, &mouse).is_break()
+                {
+                    return Ok(());
+                }
+            }
+
+            let step = settings.step();
+            accumulator += dt;
+
+            let mut ticks = 0;
+            while accumulator >= step {
+                if ticks == settings.max_ticks {
+                    accumulator = Duration::ZERO;
+                    break;
+                }
+
+                if app.fixed_update(self, step).is_break() {
+                    return Ok(());
+                }
+
+                accumulator -= step;
+                ticks += 1;
+            }
+            self.frame_times.ticks = ticks;
+
+            if app.update(self, dt).is_break() {
+                return Ok(());
+            }
+
+            app.draw(self, accumulator.as_secs_f64() / step.as_secs_f64());
+
+            self.submit_plugin_draws();
+            self.render()?;
+            self.present();
+            self.input.end_frame();
+
+            if let Some(fps) = settings.max_fps {
+                let target = Duration::from_secs(1) / fps.max(1);
+                if let Some(remaining) = target.checked_sub(frame_start.elapsed()) {
+                    thread::sleep(remaining);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use std::iter;
+
+use crate::char::{Char437, Shade};
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+
+/// The glyphs used for each part of a box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxChars {
+    pub top_left :     Char437,
+    pub top :          Char437,
+    pub top_right :    Char437,
+    pub left :         Char437,
+    pub right :        Char437,
+    pub bottom_left :  Char437,
+    pub bottom :       Char437,
+    pub bottom_right : Char437,
+}
+
+impl BoxChars {
+    /// Every part of the box drawn with the same glyph.
+    #[must_use]
+    pub const fn uniform(chr : Char437) -> Self {
+        Self {
+            top_left :     chr,
+            top :          chr,
+            top_right :    chr,
+            left :         chr,
+            right :        chr,
+            bottom_left :  chr,
+            bottom :       chr,
+            bottom_right : chr,
+        }
+    }
+}
+
+/// How the border of a box is drawn.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BoxStyle {
+    /// `┌─┐`
+    #[default]
+    Single,
+    /// `╔═╗`
+    Double,
+    /// `╒═╕`, double horizontal edges with single vertical edges.
+    DoubleHorizontal,
+    /// `╓─╖`, single horizontal edges with double vertical edges.
+    DoubleVertical,
+    /// Every edge drawn with a shade or block glyph.
+    Shade(Shade),
+    Custom(BoxChars),
+}
+
+impl BoxStyle {
+    #[must_use]
+    pub const fn chars(self) -> BoxChars {
+        match self {
+            Self::Single => line_chars(1, 1),
+            Self::Double => line_chars(2, 2),
+            Self::DoubleHorizontal => line_chars(2, 1),
+            Self::DoubleVertical => line_chars(1, 2),
+            Self::Shade(shade) => BoxChars::uniform(shade.glyph()),
+            Self::Custom(chars) => chars,
+        }
+    }
+
+    /// Whether the style is drawn with line glyphs, which join up with the
+    /// lines already on a console.
+    #[must_use]
+    pub const fn is_line(self) -> bool {
+        matches!(
+            self,
+            Self::Single | Self::Double | Self::DoubleHorizontal | Self::DoubleVertical
+        )
+    }
+}
+
+/// The arms of a line glyph, in the order up, down, left, right. Each arm is
+/// `0` for none, `1` for single and `2` for double.
+type Arms = [u8; 4];
+
+#[rustfmt::skip]
+const LINE_GLYPHS : [(u8, Arms); 40] = [
+    (179, [1, 1, 0, 0]), (180, [1, 1, 1, 0]), (181, [1, 1, 2, 0]), (182, [2, 2, 1, 0]),
+    (183, [0, 2, 1, 0]), (184, [0, 1, 2, 0]), (185, [2, 2, 2, 0]), (186, [2, 2, 0, 0]),
+    (187, [0, 2, 2, 0]), (188, [2, 0, 2, 0]), (189, [2, 0, 1, 0]), (190, [1, 0, 2, 0]),
+    (191, [0, 1, 1, 0]), (192, [1, 0, 0, 1]), (193, [1, 0, 1, 1]), (194, [0, 1, 1, 1]),
+    (195, [1, 1, 0, 1]), (196, [0, 0, 1, 1]), (197, [1, 1, 1, 1]), (198, [1, 1, 0, 2]),
+    (199, [2, 2, 0, 1]), (200, [2, 0, 0, 2]), (201, [0, 2, 0, 2]), (202, [2, 0, 2, 2]),
+    (203, [0, 2, 2, 2]), (204, [2, 2, 0, 2]), (205, [0, 0, 2, 2]), (206, [2, 2, 2, 2]),
+    (207, [1, 0, 2, 2]), (208, [2, 0, 1, 1]), (209, [0, 1, 2, 2]), (210, [0, 2, 1, 1]),
+    (211, [2, 0, 0, 1]), (212, [1, 0, 0, 2]), (213, [0, 1, 0, 2]), (214, [0, 2, 0, 1]),
+    (215, [2, 2, 1, 1]), (216, [1, 1, 2, 2]), (217, [1, 0, 1, 0]), (218, [0, 1, 0, 1]),
+];
+
+const fn arms(chr : Char437) -> Option<Arms> {
+    let mut idx = 0;
+    while idx < LINE_GLYPHS.len() {
+        if LINE_GLYPHS[idx].0 == chr.0 {
+            return Some(LINE_GLYPHS[idx].1);
+        }
+        idx += 1;
+    }
+    None
+}
+
+const fn from_arms(arms : Arms) -> Option<Char437> {
+    let mut idx = 0;
+    while idx < LINE_GLYPHS.len() {
+        let [up, down, left, right] = LINE_GLYPHS[idx].1;
+        if up == arms[0] && down == arms[1] && left == arms[2] && right == arms[3] {
+            return Some(Char437(LINE_GLYPHS[idx].0));
+        }
+        idx += 1;
+    }
+    None
+}
+
+/// The line glyphs of a box with horizontal edges of weight `horizontal` and
+/// vertical edges of weight `vertical`.
+const fn line_chars(horizontal : u8, vertical : u8) -> BoxChars {
+    const fn glyph(arms : Arms) -> Char437 {
+        match from_arms(arms) {
+            Some(chr) => chr,
+            None => Char437(b'+'),
+        }
+    }
+
+    BoxChars {
+        top_left :     glyph([0, vertical, 0, horizontal]),
+        top :          glyph([0, 0, horizontal, horizontal]),
+        top_right :    glyph([0, vertical, horizontal, 0]),
+        left :         glyph([vertical, vertical, 0, 0]),
+        right :        glyph([vertical, vertical, 0, 0]),
+        bottom_left :  glyph([vertical, 0, 0, horizontal]),
+        bottom :       glyph([0, 0, horizontal, horizontal]),
+        bottom_right : glyph([vertical, 0, horizontal, 0]),
+    }
+}
+
+/// Joins two line glyphs drawn over each other into the junction joining
+/// both of them, the arms of `over` taking precedence where they overlap.
+///
+/// Returns `None` if either glyph isn't a line glyph.
+#[must_use]
+pub fn join_lines(under : Char437, over : Char437) -> Option<Char437> {
+    let (under, over) = (arms(under)?, arms(over)?);
+
+    let mut joined = [0; 4];
+    for (arm, (under, over)) in joined.iter_mut().zip(under.into_iter().zip(over)) {
+        *arm = if over == 0 { under } else { over };
+    }
+
+    // Codepage 437 has no glyphs for lines changing weight as they pass
+    // through a cell, so each axis takes its heaviest arm.
+    for (a, b) in [(0, 1), (2, 3)] {
+        if joined[a] != 0 && joined[b] != 0 {
+            let weight = joined[a].max(joined[b]);
+            joined[a] = weight;
+            joined[b] = weight;
+        }
+    }
+
+    from_arms(joined)
+}
+
+/// The cells making up the border of `rect`, going around its edges only.
+pub(crate) fn box_cells(
+    rect : CellRect,
+    chars : BoxChars,
+) -> impl Iterator<Item = ((i32, i32), Char437)> {
+    let (left, top) = (rect.x, rect.y);
+    let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+
+    let rows = iter::once(top).chain((bottom != top).then_some(bottom));
+    let columns = iter::once(left).chain((right != left).then_some(right));
+
+    let edges = rows
+        .flat_map(move |y| (left ..= right).map(move |x| (x, y)))
+        .chain((top + 1 .. bottom).flat_map(move |y| columns.clone().map(move |x| (x, y))));
+
+    (!rect.is_empty())
+        .then_some(edges)
+        .into_iter()
+        .flatten()
+        .map(move |(x, y)| {
+            let chr = match (x == left, x == right, y == top, y == bottom) {
+                (true, _, true, _) => chars.top_left,
+                (_, true, true, _) => chars.top_right,
+                (true, _, _, true) => chars.bottom_left,
+                (_, true, _, true) => chars.bottom_right,
+                (_, _, true, _) => chars.top,
+                (_, _, _, true) => chars.bottom,
+                (true, ..) => chars.left,
+                _ => chars.right,
+            };
+
+            ((x, y), chr)
+        })
+}
+
+/// The title as set into the top edge of `rect`, cut short if the box is too
+/// narrow for it. `None` if it doesn't fit at all.
+pub(crate) fn box_title(rect : CellRect, title : &str) -> Option<String> {
+    let room = usize::try_from(rect.width - 4).unwrap_or_default();
+
+    (room > 2 && !title.is_empty()).then(|| {
+        let title : String = title.chars().take(room.saturating_sub(2)).collect();
+        format!(" {title} ")
+    })
+}
+
+impl Console {
+    /// Draws the border of `rect`. Line styles join up with any lines already
+    /// on the console, so boxes sharing an edge get proper junctions.
+    pub fn draw_box(
+        &mut self,
+        rect : impl Into<CellRect>,
+        style : BoxStyle,
+        palette : impl Into<Palette>,
+    ) {
+        let palette = palette.into();
+
+        for (pos, chr) in box_cells(rect.into(), style.chars()) {
+            let chr = if style.is_line() {
+                self.get(pos)
+                    .and_then(|cell| join_lines(cell.glyph, chr))
+                    .unwrap_or(chr)
+            } else {
+                chr
+            };
+
+            self.set(pos, chr, palette);
+        }
+    }
+
+    /// Draws the border of `rect` with `title` set into the top edge, cut
+    /// short if the box is too narrow for it.
+    pub fn draw_titled_box(
+        &mut self,
+        rect : impl Into<CellRect>,
+        style : BoxStyle,
+        palette : impl Into<Palette>,
+        title : &str,
+    ) {
+        let rect = rect.into();
+        let palette = palette.into();
+
+        self.draw_box(rect, style, palette);
+
+        if let Some(title) = box_title(rect, title) {
+            self.print((rect.x + 2, rect.y), &title, palette);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::char::CP437CharExt;
+    use crate::color::Color;
+
+    fn glyph(chr : char) -> Option<Char437> {
+        chr.to_cp437()
+    }
+
+    fn join(under : char, over : char) -> Option<char> {
+        join_lines(glyph(under)?, glyph(over)?).map(char::from)
+    }
+
+    #[test]
+    fn crossing_lines_join() {
+        assert_eq!(join('─', '│'), Some('┼'));
+        assert_eq!(join('┐', '└'), Some('┼'));
+        assert_eq!(join('│', '═'), Some('╪'));
+        assert_eq!(join('║', '─'), Some('╫'));
+    }
+
+    #[test]
+    fn over_arms_take_precedence() {
+        assert_eq!(join('═', '─'), Some('─'));
+        assert_eq!(join('─', '═'), Some('═'));
+    }
+
+    #[test]
+    fn each_axis_takes_its_heaviest_arm() {
+        assert_eq!(join('║', '┌'), Some('╟'));
+    }
+
+    #[test]
+    fn only_line_glyphs_join() {
+        assert_eq!(join('#', '─'), None);
+        assert_eq!(join('─', '▓'), None);
+    }
+
+    #[test]
+    fn box_cells_cover_the_edges_only() {
+        let chars = BoxStyle::Single.chars();
+
+        assert_eq!(box_cells(CellRect::new(0, 0, 4, 3), chars).count(), 10);
+        assert_eq!(box_cells(CellRect::new(0, 0, 1, 3), chars).count(), 3);
+        assert_eq!(box_cells(CellRect::new(0, 0, 1, 1), chars).count(), 1);
+        assert_eq!(box_cells(CellRect::new(0, 0, 0, 3), chars).count(), 0);
+    }
+
+    #[test]
+    fn boxes_sharing_an_edge_join() {
+        let mut console = Console::new(5, 3);
+
+        console.draw_box((0, 0, 3, 3), BoxStyle::Single, Color::new(255, 255, 255));
+        console.draw_box((2, 0, 3, 3), BoxStyle::Single, Color::new(255, 255, 255));
+
+        assert_eq!(console.to_text(), "┌─┬─┐\n│ │ │\n└─┴─┘");
+    }
+
+    #[test]
+    fn titles_are_cut_short() {
+        let mut console = Console::new(10, 2);
+
+        console.draw_titled_box(
+            (0, 0, 10, 2),
+            BoxStyle::Double,
+            Color::new(255, 255, 255),
+            "Inventory",
+        );
+
+        assert_eq!(console.to_text(), "╔═ Inve ═╗\n╚════════╝");
+        assert_eq!(box_title(CellRect::new(0, 0, 6, 2), "Inventory"), None);
+    }
+}
@@ -28,6 +28,39 @@ impl Char437 {
     pub const fn offset(&self) -> (u8, u8) {
         (self.0 % 16, self.0 / 16)
     }
+
+    /// Whether the glyph is empty in a standard codepage 437 font, meaning
+    /// there is nothing to draw for it.
+    #[must_use]
+    pub const fn is_blank(&self) -> bool {
+        matches!(self.0, 0 | 32 | 255)
+    }
+}
+
+/// The shade and block glyphs, from lightest to solid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Shade {
+    /// `░`
+    Light,
+    /// `▒`
+    Medium,
+    /// `▓`
+    #[default]
+    Dark,
+    /// `█`
+    Full,
+}
+
+impl Shade {
+    #[must_use]
+    pub const fn glyph(self) -> Char437 {
+        match self {
+            Self::Light => Char437(176),
+            Self::Medium => Char437(177),
+            Self::Dark => Char437(178),
+            Self::Full => Char437(219),
+        }
+    }
 }
 
 impl From<u8> for Char437 {
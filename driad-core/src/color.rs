@@ -1,9 +1,12 @@
 use std::fmt;
+use std::num::ParseIntError;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::str::FromStr;
 
 use image::{Rgb, Rgba};
 use sdl3::pixels;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
 /// A RGB color.
@@ -19,6 +22,84 @@ impl Color {
     pub const fn new(r : u8, g : u8, b : u8) -> Self {
         Self { r, g, b }
     }
+
+    /// The color at half brightness, as used for disabled or backgrounded
+    /// UI.
+    #[must_use]
+    pub const fn dimmed(self) -> Self {
+        Self::new(self.r / 2, self.g / 2, self.b / 2)
+    }
+
+    /// Looks up one of the colors in [`NAMED_COLORS`], ignoring case.
+    #[must_use]
+    pub fn named(name : &str) -> Option<Self> {
+        NAMED_COLORS
+            .iter()
+            .find(|(named, _)| named.eq_ignore_ascii_case(name))
+            .map(|(_, color)| *color)
+    }
+}
+
+/// The colors that can be referred to by name, the 16 colors of the VGA text
+/// mode palette along with a few extras.
+pub const NAMED_COLORS : [(&str, Color); 21] = [
+    ("black", Color::new(0, 0, 0)),
+    ("blue", Color::new(0, 0, 170)),
+    ("green", Color::new(0, 170, 0)),
+    ("cyan", Color::new(0, 170, 170)),
+    ("red", Color::new(170, 0, 0)),
+    ("magenta", Color::new(170, 0, 170)),
+    ("brown", Color::new(170, 85, 0)),
+    ("light_gray", Color::new(170, 170, 170)),
+    ("gray", Color::new(85, 85, 85)),
+    ("light_blue", Color::new(85, 85, 255)),
+    ("light_green", Color::new(85, 255, 85)),
+    ("light_cyan", Color::new(85, 255, 255)),
+    ("light_red", Color::new(255, 85, 85)),
+    ("light_magenta", Color::new(255, 85, 255)),
+    ("yellow", Color::new(255, 255, 85)),
+    ("white", Color::new(255, 255, 255)),
+    ("grey", Color::new(85, 85, 85)),
+    ("light_grey", Color::new(170, 170, 170)),
+    ("orange", Color::new(255, 170, 0)),
+    ("purple", Color::new(128, 0, 128)),
+    ("pink", Color::new(255, 170, 200)),
+];
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses a hex color of the form `#rgb` or `#rrggbb`, or one of the
+    /// [`NAMED_COLORS`].
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        let Some(hex) = s.strip_prefix('#') else {
+            return Self::named(s).ok_or_else(|| ParseColorError::UnknownName(s.to_owned()));
+        };
+
+        let channel = |idx : usize, len : usize| {
+            let digits = hex.get(idx * len .. (idx + 1) * len).unwrap_or_default();
+            // `#abc` is short for `#aabbcc`
+            u8::from_str_radix(&digits.repeat(3 - len), 16)
+        };
+
+        match hex.len() {
+            3 => Ok(Self::new(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?)),
+            6 => Ok(Self::new(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+            _ => Err(ParseColorError::BadLength(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseColorError {
+    #[error("Unknown color name: {0}")]
+    UnknownName(String),
+
+    #[error("Hex colors must be #rgb or #rrggbb, got {0}")]
+    BadLength(String),
+
+    #[error(transparent)]
+    ParseIntError(#[from] ParseIntError),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
@@ -30,9 +111,25 @@ pub struct Palette {
     pub bg :         Color,
     pub bg_accent1 : Option<Color>,
     pub bg_accent2 : Option<Color>,
+
+    /// When set the background is left undrawn, letting whatever is beneath a
+    /// glyph show through.
+    #[serde(default)]
+    pub transparent : bool,
 }
 
 impl Palette {
+    /// The background to fill behind a glyph, if any.
+    #[inline]
+    #[must_use]
+    pub const fn bg_fill(&self) -> Option<Color> {
+        if self.transparent {
+            None
+        } else {
+            Some(self.bg)
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn bg_accent2(&self) -> Color {
@@ -76,6 +173,8 @@ impl Default for Palette {
             fg_accent2 : None,
             bg_accent1 : None,
             bg_accent2 : None,
+
+            transparent : false,
         }
     }
 }
@@ -90,6 +189,55 @@ impl Palette {
             fg_accent2 : None,
             bg_accent1 : None,
             bg_accent2 : None,
+
+            transparent : false,
+        }
+    }
+
+    /// A palette that only draws the foreground.
+    #[must_use]
+    pub fn transparent(fg : Color) -> Self {
+        Self::from(fg).with_transparent_bg()
+    }
+
+    #[must_use]
+    pub const fn with_transparent_bg(mut self) -> Self {
+        self.transparent = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_accent1(mut self, fg : Color, bg : Color) -> Self {
+        self.fg_accent1 = Some(fg);
+        self.bg_accent1 = Some(bg);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_accent2(mut self, fg : Color, bg : Color) -> Self {
+        self.fg_accent2 = Some(fg);
+        self.bg_accent2 = Some(bg);
+        self
+    }
+
+    /// The palette drawn with the first accents as its colors, as used for
+    /// focused widgets.
+    #[must_use]
+    pub fn accent1(&self) -> Self {
+        Self {
+            fg : self.fg_accent1(),
+            bg : self.bg_accent1(),
+            ..*self
+        }
+    }
+
+    /// The palette drawn with the second accents as its colors.
+    #[must_use]
+    pub fn accent2(&self) -> Self {
+        Self {
+            fg : self.fg_accent2(),
+            bg : self.bg_accent2(),
+            ..*self
         }
     }
 }
@@ -229,3 +377,44 @@ impl From<Color> for Rgba<u8> {
         Self([value.r, value.g, value.b, 255])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_colors_parse() -> Result<(), ParseColorError> {
+        assert_eq!("#ff8000".parse::<Color>()?, Color::new(255, 128, 0));
+        assert_eq!("#F80".parse::<Color>()?, Color::new(255, 136, 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn named_colors_parse_ignoring_case() -> Result<(), ParseColorError> {
+        assert_eq!("light_red".parse::<Color>()?, Color::new(255, 85, 85));
+        assert_eq!("Grey".parse::<Color>()?, "gray".parse::<Color>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bad_colors_are_errors() {
+        assert!(matches!(
+            "#ff80".parse::<Color>(),
+            Err(ParseColorError::BadLength(_))
+        ));
+        assert!(matches!(
+            "#ggg".parse::<Color>(),
+            Err(ParseColorError::ParseIntError(_))
+        ));
+        assert!(matches!(
+            "#ééé".parse::<Color>(),
+            Err(ParseColorError::ParseIntError(_))
+        ));
+        assert!(matches!(
+            "ff8000".parse::<Color>(),
+            Err(ParseColorError::UnknownName(_))
+        ));
+    }
+}
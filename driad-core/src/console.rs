@@ -0,0 +1,382 @@
+use crate::char::{CP437CharExt, Char437};
+use crate::color::{Color, Palette};
+
+/// A rectangle of cells on a [`Console`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellRect {
+    pub x :      i32,
+    pub y :      i32,
+    pub width :  i32,
+    pub height : i32,
+}
+
+impl CellRect {
+    #[must_use]
+    #[inline]
+    pub const fn new(x : i32, y : i32, width : i32, height : i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The column just past the right edge of the rectangle.
+    #[must_use]
+    #[inline]
+    pub const fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    /// The row just past the bottom edge of the rectangle.
+    #[must_use]
+    #[inline]
+    pub const fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn contains(&self, (x, y) : (i32, i32)) -> bool {
+        x >= self.x && x < self.right() && y >= self.y && y < self.bottom()
+    }
+
+    /// The overlapping area of two rectangles, which may be empty.
+    #[must_use]
+    pub fn intersection(&self, other : Self) -> Self {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+
+        Self {
+            x,
+            y,
+            width : (self.right().min(other.right()) - x).max(0),
+            height : (self.bottom().min(other.bottom()) - y).max(0),
+        }
+    }
+
+    /// Iterates over every cell position inside the rectangle, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (i32, i32)> + use<> {
+        let Self {
+            x,
+            y,
+            width,
+            height,
+        } = *self;
+
+        (y .. y + height.max(0))
+            .flat_map(move |row| (x .. x + width.max(0)).map(move |col| (col, row)))
+    }
+}
+
+impl From<(i32, i32, i32, i32)> for CellRect {
+    fn from((x, y, width, height) : (i32, i32, i32, i32)) -> Self {
+        Self::new(x, y, width, height)
+    }
+}
+
+/// A single character cell of a [`Console`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph : Char437,
+    pub fg :    Color,
+    /// The background of the cell, `None` leaves it transparent.
+    pub bg :    Option<Color>,
+}
+
+impl Cell {
+    #[must_use]
+    pub fn new(glyph : Char437, palette : impl Into<Palette>) -> Self {
+        let palette = palette.into();
+
+        Self {
+            glyph,
+            fg : palette.fg,
+            bg : palette.bg_fill(),
+        }
+    }
+
+    /// The palette the cell is drawn with.
+    #[must_use]
+    pub fn palette(&self) -> Palette {
+        self.bg.map_or_else(
+            || Palette::transparent(self.fg),
+            |bg| Palette::simple(self.fg, bg),
+        )
+    }
+}
+
+impl Default for Cell {
+    /// An empty cell with a transparent background.
+    fn default() -> Self {
+        Self::new(Char437(0), Palette::default().with_transparent_bg())
+    }
+}
+
+/// An off-screen grid of [`Cell`]s which is drawn onto a canvas in a single
+/// pass by [`Font::flush`](crate::font::Font::flush).
+///
+/// Writes outside of the console are silently clipped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Console {
+    width :  i32,
+    height : i32,
+
+    cells : Vec<Cell>,
+}
+
+impl Console {
+    /// The most cells a console holds, far more than any window shows.
+    pub const MAX_CELLS : usize = 1 << 24;
+
+    /// Creates a console of blank cells, negative sizes are treated as zero.
+    /// The height is cut down so that there are at most [`Self::MAX_CELLS`]
+    /// cells.
+    #[must_use]
+    pub fn new(width : i32, height : i32) -> Self {
+        let width = width.max(0);
+        let rows = Self::MAX_CELLS / usize::try_from(width).unwrap_or(0).max(1);
+        let height = height.clamp(0, i32::try_from(rows).unwrap_or(i32::MAX));
+
+        let cells = usize::try_from(width)
+            .ok()
+            .zip(usize::try_from(height).ok())
+            .and_then(|(width, height)| width.checked_mul(height))
+            .unwrap_or_default();
+
+        Self {
+            width,
+            height,
+            cells : vec![Cell::default(); cells],
+        }
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn width(&self) -> i32 {
+        self.width
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// The area covered by the console, with the origin at `(0, 0)`.
+    #[must_use]
+    #[inline]
+    pub const fn rect(&self) -> CellRect {
+        CellRect::new(0, 0, self.width, self.height)
+    }
+
+    fn index(&self, (x, y) : (i32, i32)) -> Option<usize> {
+        if self.rect().contains((x, y)) {
+            usize::try_from(y * self.width + x).ok()
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, pos : impl Into<(i32, i32)>) -> Option<&Cell> {
+        self.cells.get(self.index(pos.into())?)
+    }
+
+    #[must_use]
+    pub fn get_mut(&mut self, pos : impl Into<(i32, i32)>) -> Option<&mut Cell> {
+        let idx = self.index(pos.into())?;
+        self.cells.get_mut(idx)
+    }
+
+    /// Sets the glyph and colors of a single cell.
+    pub fn set(
+        &mut self,
+        pos : impl Into<(i32, i32)>,
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        if let Some(cell) = self.get_mut(pos) {
+            *cell = Cell::new(glyph, palette);
+        }
+    }
+
+    /// Resets every cell to a blank [`Cell::default`].
+    pub fn clear(&mut self) {
+        self.cells.fill(Cell::default());
+    }
+
+    /// Writes `text` left to right starting at `pos`. Chars that don't exist in
+    /// codepage 437 are written as `?`.
+    pub fn print(
+        &mut self,
+        pos : impl Into<(i32, i32)>,
+        text : &str,
+        palette : impl Into<Palette>,
+    ) {
+        let (x, y) = pos.into();
+        let palette = palette.into();
+
+        for (col, chr) in (x ..).zip(text.chars()) {
+            self.set((col, y), chr.to_cp437().unwrap_or(Char437(b'?')), palette);
+        }
+    }
+
+    /// Sets every cell inside `rect` to the same glyph and colors.
+    pub fn fill(
+        &mut self,
+        rect : impl Into<CellRect>,
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        let cell = Cell::new(glyph, palette);
+
+        for pos in rect.into().intersection(self.rect()).positions() {
+            if let Some(target) = self.get_mut(pos) {
+                *target = cell;
+            }
+        }
+    }
+
+    /// The glyphs of the console as UTF-8 text, one line per row. Blank glyphs
+    /// are written as spaces.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        self.cells
+            .chunks(usize::try_from(self.width).unwrap_or_default().max(1))
+            .map(|row| {
+                row.iter()
+                    .map(|cell| {
+                        if cell.glyph.is_blank() {
+                            ' '
+                        } else {
+                            char::from(cell.glyph)
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Iterates over every cell along with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((i32, i32), &Cell)> {
+        self.rect().positions().zip(self.cells.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph_at(console : &Console, pos : (i32, i32)) -> Option<Char437> {
+        console.get(pos).map(|cell| cell.glyph)
+    }
+
+    #[test]
+    fn negative_sizes_are_empty() {
+        let console = Console::new(-3, 5);
+
+        assert_eq!((console.width(), console.height()), (0, 5));
+        assert_eq!(console.iter().count(), 0);
+    }
+
+    #[test]
+    fn huge_sizes_are_capped() {
+        let console = Console::new(i32::MAX, i32::MAX);
+
+        assert_eq!(console.width(), i32::MAX);
+        assert!(console.iter().count() <= Console::MAX_CELLS);
+
+        let console = Console::new(4096, i32::MAX);
+        assert_eq!(
+            usize::try_from(console.height()).ok(),
+            Some(Console::MAX_CELLS / 4096)
+        );
+    }
+
+    #[test]
+    fn set_and_get_clip_to_the_console() {
+        let mut console = Console::new(3, 2);
+
+        console.set((2, 1), Char437(b'@'), Color::new(255, 0, 0));
+        console.set((3, 1), Char437(b'x'), Color::new(255, 0, 0));
+        console.set((-1, 0), Char437(b'x'), Color::new(255, 0, 0));
+
+        assert_eq!(glyph_at(&console, (2, 1)), Some(Char437(b'@')));
+        assert_eq!(
+            console.get((2, 1)).map(|cell| cell.fg),
+            Some(Color::new(255, 0, 0))
+        );
+        assert_eq!(glyph_at(&console, (3, 1)), None);
+        assert_eq!(glyph_at(&console, (-1, 0)), None);
+        assert_eq!(console.to_text(), "   \n  @");
+    }
+
+    #[test]
+    fn print_clips_and_replaces_unknown_chars() {
+        let mut console = Console::new(4, 1);
+
+        console.print((-1, 0), "ab€de", Color::new(255, 255, 255));
+
+        assert_eq!(console.to_text(), "b?de");
+    }
+
+    #[test]
+    fn fill_clips_to_the_console() {
+        let mut console = Console::new(3, 3);
+
+        console.fill(
+            CellRect::new(1, -1, 5, 3),
+            Char437(b'#'),
+            Palette::simple(Color::new(255, 255, 255), Color::new(0, 0, 255)),
+        );
+
+        assert_eq!(console.to_text(), " ##\n ##\n   ");
+        assert_eq!(
+            console.get((1, 0)).and_then(|cell| cell.bg),
+            Some(Color::new(0, 0, 255))
+        );
+        assert_eq!(console.get((0, 0)).and_then(|cell| cell.bg), None);
+    }
+
+    #[test]
+    fn clear_blanks_every_cell() {
+        let mut console = Console::new(2, 2);
+
+        console.print((0, 0), "ab", Color::new(255, 255, 255));
+        console.clear();
+
+        assert!(console.iter().all(|(_, cell)| *cell == Cell::default()));
+    }
+
+    #[test]
+    fn to_text_writes_one_line_per_row() {
+        let mut console = Console::new(3, 2);
+
+        console.print((0, 0), "ab", Color::new(255, 255, 255));
+        console.print((1, 1), "☺", Color::new(255, 255, 255));
+
+        assert_eq!(console.to_text(), "ab \n ☺ ");
+    }
+
+    #[test]
+    fn rect_intersection_and_positions() {
+        let rect = CellRect::new(0, 0, 4, 4).intersection(CellRect::new(2, 3, 5, 5));
+
+        assert_eq!(rect, CellRect::new(2, 3, 2, 1));
+        assert_eq!(rect.positions().collect::<Vec<_>>(), vec![(2, 3), (3, 3)]);
+        assert!(
+            CellRect::new(0, 0, 2, 2)
+                .intersection(CellRect::new(5, 5, 1, 1))
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::ops::{Deref, DerefMut};
+use std::vec;
+
+use sdl3::render::{Canvas, RenderTarget};
+
+use crate::border::{BoxStyle, box_cells, box_title};
+use crate::char::{CP437CharExt, Char437};
+use crate::color::Palette;
+use crate::console::CellRect;
+use crate::font::{Font, FontKey, PutGlyphError};
+use crate::markup::Span;
+use crate::text::TextLayout;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum DrawCommand {
+    PutChr {
+        pos :     (i32, i32),
+        chr :     Char437,
+        palette : Palette,
+    },
+    /// A string laid out left to right on a single row.
+    PutStr {
+        pos :     (i32, i32),
+        text :    String,
+        palette : Palette,
+    },
+    /// Runs of differently colored text on a single row, see
+    /// [`markup::parse`](crate::markup::parse).
+    PutSpans {
+        pos :   (i32, i32),
+        spans : Vec<Span>,
+    },
+    /// Text laid out inside a rectangle, see [`TextLayout`].
+    PutText {
+        rect :    CellRect,
+        text :    String,
+        layout :  TextLayout,
+        palette : Palette,
+    },
+    /// A rectangle filled with a single glyph.
+    FillRect {
+        rect :    CellRect,
+        chr :     Char437,
+        palette : Palette,
+    },
+    /// The border of a rectangle, with an optional title in the top edge.
+    DrawBox {
+        rect :    CellRect,
+        style :   BoxStyle,
+        title :   Option<String>,
+        palette : Palette,
+    },
+    /// An icon from one of the font's extensions.
+    PutIcon {
+        pos :       (i32, i32),
+        extension : String,
+        key :       String,
+        palette :   Palette,
+    },
+    #[default]
+    None,
+}
+
+impl DrawCommand {
+    /// Draws the command onto the canvas with `font`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Font::put`].
+    pub fn execute<T : RenderTarget>(
+        &self,
+        font : &Font,
+        canvas : &mut Canvas<T>,
+    ) -> Result<(), PutGlyphError> {
+        match self {
+            Self::PutChr { pos, chr, palette } => font.put_char437(canvas, *chr, *pos, *palette),
+            Self::PutStr { pos, text, palette } => font.put_str(canvas, text, *pos, *palette),
+            Self::PutSpans { pos, spans } => font.put_spans(canvas, spans, *pos),
+            Self::PutText {
+                rect,
+                text,
+                layout,
+                palette,
+            } => {
+                font.put_text(canvas, *rect, text, layout, *palette)
+                    .map(|_| ())
+            },
+            Self::FillRect { rect, chr, palette } => {
+                rect.positions()
+                    .try_for_each(|pos| font.put_char437(canvas, *chr, pos, *palette))
+            },
+            Self::DrawBox {
+                rect,
+                style,
+                title,
+                palette,
+            } => {
+                let title = title.as_deref().and_then(|title| box_title(*rect, title));
+                let title_cells = title.iter().flat_map(|title| {
+                    (rect.x + 2 ..)
+                        .zip(title.chars())
+                        .map(|(x, chr)| ((x, rect.y), chr.to_cp437().unwrap_or(Char437(b'?'))))
+                });
+                let title_end = rect.x
+                    + 2
+                    + title.as_ref().map_or(0, |title| {
+                        i32::try_from(title.chars().count()).unwrap_or(i32::MAX)
+                    });
+
+                // The title covers part of the top edge, its spaces included
+                box_cells(*rect, style.chars())
+                    .filter(|&((x, y), _)| y != rect.y || !(rect.x + 2 .. title_end).contains(&x))
+                    .filter(|(_, glyph)| !glyph.is_blank())
+                    .chain(title_cells)
+                    .try_for_each(|(pos, glyph)| font.put_char437(canvas, glyph, pos, *palette))
+            },
+            Self::PutIcon {
+                pos,
+                extension,
+                key,
+                palette,
+            } => {
+                font.put(
+                    canvas,
+                    FontKey::Icon(extension.clone(), key.clone()),
+                    *pos,
+                    *palette,
+                )
+            },
+            Self::None => Ok(()),
+        }
+    }
+}
+
+/// A [`DrawCommand`] along with where it sits in the frame.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DrawItem {
+    /// The index of the [`Layer`](crate::layer::Layer) to draw onto, commands
+    /// are drawn after the layer's console.
+    pub layer :   usize,
+    /// Commands on the same layer are drawn in ascending `z` order.
+    pub z :       i32,
+    pub command : DrawCommand,
+}
+
+impl DrawItem {
+    #[must_use]
+    pub const fn new(command : DrawCommand) -> Self {
+        Self {
+            layer : 0,
+            z : 0,
+            command,
+        }
+    }
+
+    #[must_use]
+    pub const fn on_layer(mut self, layer : usize) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_z(mut self, z : i32) -> Self {
+        self.z = z;
+        self
+    }
+}
+
+impl From<DrawCommand> for DrawItem {
+    fn from(command : DrawCommand) -> Self {
+        Self::new(command)
+    }
+}
+
+/// A batch of draw commands, handed to [`Driad::submit`](crate::Driad::submit)
+/// to be drawn on the next frame.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DrawPass {
+    commands : Vec<DrawItem>,
+}
+
+impl DrawPass {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            commands : Vec::new(),
+        }
+    }
+}
+
+impl DerefMut for DrawPass {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.commands
+    }
+}
+
+impl Deref for DrawPass {
+    type Target = Vec<DrawItem>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.commands
+    }
+}
+
+impl From<Vec<DrawItem>> for DrawPass {
+    fn from(commands : Vec<DrawItem>) -> Self {
+        Self { commands }
+    }
+}
+
+impl<I : Into<DrawItem>> FromIterator<I> for DrawPass {
+    fn from_iter<T : IntoIterator<Item = I>>(iter : T) -> Self {
+        Self {
+            commands : iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl IntoIterator for DrawPass {
+    type IntoIter = vec::IntoIter<DrawItem>;
+    type Item = DrawItem;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.commands.into_iter()
+    }
+}
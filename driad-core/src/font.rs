@@ -1,18 +1,28 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::num::TryFromIntError;
 use std::ops::Deref;
 use std::path::Path;
+use std::ptr;
 
-use image::{EncodableLayout, GenericImage, GenericImageView, ImageError, Rgb, RgbImage, SubImage};
+use image::{
+    EncodableLayout, GenericImage, GenericImageView, ImageError, Rgb, RgbImage, Rgba, RgbaImage,
+    SubImage,
+};
 use log::trace;
 use sdl3::pixels::PixelFormat;
 use sdl3::rect::Rect;
-use sdl3::render::{Canvas, RenderTarget, TextureValueError, UpdateTextureError};
+use sdl3::render::{
+    BlendMode, Canvas, RenderTarget, Texture, TextureValueError, UpdateTextureError,
+};
+use sdl3::sys::render::{SDL_GetRendererProperties, SDL_Renderer};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::char::Char437;
 use crate::color::{Color, Palette};
+use crate::console::Console;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LookupTable {
@@ -37,6 +47,107 @@ pub struct Font {
     atlas : RgbImage,
 
     extensions : HashMap<String, (LookupTable, RgbImage)>,
+
+    /// Textures uploaded from `atlas` and `extensions`, created lazily the
+    /// first time the font is drawn onto each canvas, oldest first.
+    cache : RefCell<Vec<FontTextures>>,
+}
+
+/// The most renderers a [`Font`] keeps textures for at once, such as a window
+/// and the surfaces screenshots are drawn onto.
+const MAX_RENDERERS : usize = 4;
+
+/// The GPU side copy of a [`Font`], tied to the renderer it was uploaded to.
+struct FontTextures {
+    /// The renderer the textures belong to.
+    renderer : RendererId,
+
+    atlas : Texture,
+
+    extensions : HashMap<String, ExtensionTextures>,
+}
+
+impl FontTextures {
+    /// Frees the textures, unless their renderer was destroyed and freed them
+    /// along with itself.
+    fn destroy(self) {
+        if !self.renderer.is_alive() {
+            return;
+        }
+
+        // SAFETY: The renderer owning the textures is still alive, and the
+        // textures are only held here.
+        unsafe {
+            self.atlas.destroy();
+
+            for textures in self.extensions.into_values() {
+                textures.destroy();
+            }
+        }
+    }
+}
+
+/// Identifies a renderer for as long as the program runs. The address of a
+/// destroyed renderer can be handed out again, but the id of its properties
+/// never is, so the pair is never shared by two renderers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RendererId {
+    addr :       usize,
+    properties : u32,
+}
+
+impl RendererId {
+    fn of<T : RenderTarget>(canvas : &Canvas<T>) -> Self {
+        let raw = canvas.raw();
+
+        // SAFETY: `raw` is the live renderer of `canvas`, which is borrowed for
+        // the length of the call.
+        let properties = unsafe { SDL_GetRendererProperties(raw) };
+
+        Self {
+            addr :       raw.expose_provenance(),
+            properties : properties.into(),
+        }
+    }
+
+    /// Whether the renderer hasn't been destroyed yet.
+    fn is_alive(self) -> bool {
+        let raw = ptr::with_exposed_provenance_mut::<SDL_Renderer>(self.addr);
+
+        // SAFETY: SDL checks the pointer against the renderers it has alive
+        // before reading through it, returning `0` for a destroyed one.
+        let properties = unsafe { SDL_GetRendererProperties(raw) };
+
+        u32::from(properties) == self.properties
+    }
+}
+
+/// An extension image split in two, so that only its white pixels are tinted.
+struct ExtensionTextures {
+    /// Every pixel but the white ones, drawn as is.
+    image : Texture,
+    /// The white pixels, tinted with the foreground.
+    tint :  Texture,
+}
+
+impl ExtensionTextures {
+    /// # Safety
+    ///
+    /// The renderer the textures were uploaded to must still be alive.
+    unsafe fn destroy(self) {
+        // SAFETY: Upheld by the caller.
+        unsafe {
+            self.image.destroy();
+            self.tint.destroy();
+        }
+    }
+}
+
+/// Which image of a [`Font`] a glyph is copied from.
+#[derive(Clone, Copy)]
+enum Sheet<'a> {
+    Atlas,
+    Extension(&'a str),
 }
 
 impl Font {
@@ -78,19 +189,51 @@ impl Font {
         }
 
         Ok(Self {
-            glyph_height : w / 16,
-            glyph_width :  h / 16,
+            glyph_height : h / 16,
+            glyph_width :  w / 16,
             atlas :        im.into_rgb8(),
             extensions :   HashMap::new(),
+            cache :        RefCell::new(Vec::new()),
         })
     }
 
-    /// Creates a texture from an image stored in [`self`] refrenced by some
-    /// [key](FontKey).
+    /// Adds an image of icons to the font under `name`, replacing any
+    /// extension already using that name. Icons are drawn with
+    /// [`FontKey::Icon`].
+    pub fn add_extension(
+        &mut self,
+        name : impl Into<String>,
+        table : LookupTable,
+        image : RgbImage,
+    ) {
+        let name = name.into();
+
+        // Textures of destroyed renderers were freed along with them
+        self.cache.get_mut().retain_mut(|textures| {
+            let alive = textures.renderer.is_alive();
+
+            if let Some(removed) = textures.extensions.remove(&name)
+                && alive
+            {
+                // SAFETY: The renderer is alive.
+                unsafe { removed.destroy() };
+            }
+
+            alive
+        });
+
+        self.extensions.insert(name, (table, image));
+    }
+
+    /// Puts an image stored in [`self`] refrenced by some [key](FontKey) onto
+    /// the canvas, tinting its white pixels with the foreground of the palette
+    /// and filling the background behind it unless the palette is
+    /// transparent.
     ///
     /// # Errors
     ///
-    /// This function will return an error if it is unable to create and put a
+    /// This function will return an error if the key doesn't exist in this
+    /// font, or if it is unable to upload or copy the font's
     /// [`Texture`](sdl3::render::Texture) onto the provied
     /// [`canvas`](sdl3::render::Canvas).
     pub fn put<T : RenderTarget>(
@@ -100,44 +243,24 @@ impl Font {
         pos : impl Into<(i32, i32)>,
         palette : impl Into<Palette>,
     ) -> Result<(), PutGlyphError> {
-        let sub_image = self.lookup_glyph(key).ok_or(PutGlyphError::MissingEntry)?;
-
-        let mut sub_image = sub_image.to_image();
-
-        let palette = palette.into();
-
-        for pix in sub_image.pixels_mut() {
-            if *pix == Rgb([255, 255, 255]) {
-                *pix = palette.fg.into();
-            }
+        match key.into() {
+            FontKey::Char(chr) => self.put_char437(canvas, chr, pos, palette),
+            FontKey::Icon(ext, key) => {
+                let (tab, _) = self
+                    .extensions
+                    .get(&ext)
+                    .ok_or(PutGlyphError::MissingEntry)?;
+                let (x, y) = *tab.get(&key).ok_or(PutGlyphError::MissingEntry)?;
+
+                self.blit(
+                    canvas,
+                    Sheet::Extension(&ext),
+                    (x, y),
+                    pos.into(),
+                    palette.into(),
+                )
+            },
         }
-
-        let mut texture = canvas.create_texture_static(
-            PixelFormat::RGB24,
-            self.glyph_width,
-            self.glyph_height,
-        )?;
-
-        texture.update(None, sub_image.as_bytes(), 3 * self.glyph_width as usize)?;
-
-        let (x, y) = pos.into();
-
-        canvas.copy(
-            &texture,
-            None,
-            Rect::new(
-                x * i32::try_from(self.glyph_width)?,
-                y * i32::try_from(self.glyph_height)?,
-                self.glyph_width,
-                self.glyph_height,
-            ),
-        )?;
-
-        // We can do this safely, because by passing a refrence to a canvas into this
-        // function we ensure it lives at least the lifetime of this function.
-        unsafe { texture.destroy() };
-
-        Ok(())
     }
 
     /// Puts a [`char`] onto the screen.
@@ -173,46 +296,181 @@ impl Font {
         pos : impl Into<(i32, i32)>,
         palette : impl Into<Palette>,
     ) -> Result<(), PutGlyphError> {
-        let sub_image = self.lookup_char(key);
+        let (x, y) = key.offset();
 
-        let palette = palette.into();
+        self.blit(
+            canvas,
+            Sheet::Atlas,
+            (x.into(), y.into()),
+            pos.into(),
+            palette.into(),
+        )
+    }
+
+    /// Copies a single tile of a sheet onto the canvas at a cell position,
+    /// uploading the font's textures first if this canvas hasn't seen them.
+    fn blit<T : RenderTarget>(
+        &self,
+        canvas : &mut Canvas<T>,
+        sheet : Sheet<'_>,
+        (tile_x, tile_y) : (u32, u32),
+        (x, y) : (i32, i32),
+        palette : Palette,
+    ) -> Result<(), PutGlyphError> {
+        let mut cache = self.cache.borrow_mut();
+        let cache = self.textures_for(&mut cache, canvas)?;
+
+        let dst = self.cell_rect((0, 0), (x, y))?;
+
+        if let Some(bg) = palette.bg_fill() {
+            fill_rect(canvas, bg, dst)?;
+        }
+
+        let texture = match sheet {
+            Sheet::Atlas => &mut cache.atlas,
+            Sheet::Extension(name) => {
+                let textures = match cache.extensions.entry(name.to_owned()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let (_, image) = self
+                            .extensions
+                            .get(name)
+                            .ok_or(PutGlyphError::MissingEntry)?;
+
+                        entry.insert(ExtensionTextures {
+                            image : upload_rgba(canvas, &extension_to_rgba(image))?,
+                            tint :  upload_rgba(canvas, &extension_tint(image))?,
+                        })
+                    },
+                };
+
+                // Icons keep their own colors, only white is recolored
+                self.copy_tile(canvas, &textures.image, (tile_x, tile_y), dst)?;
+                &mut textures.tint
+            },
+        };
+
+        texture.set_color_mod(palette.fg.r, palette.fg.g, palette.fg.b);
+
+        self.copy_tile(canvas, texture, (tile_x, tile_y), dst)
+    }
+
+    /// Draws every cell of a [`Console`] onto the canvas, sharing one upload
+    /// of the atlas between all of them. The console's top left cell is drawn
+    /// at the pixel `offset`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Self::put`].
+    pub fn flush<T : RenderTarget>(
+        &self,
+        canvas : &mut Canvas<T>,
+        console : &Console,
+        offset : impl Into<(i32, i32)>,
+    ) -> Result<(), PutGlyphError> {
+        let offset = offset.into();
+
+        let mut cache = self.cache.borrow_mut();
+        let textures = self.textures_for(&mut cache, canvas)?;
 
-        let mut sub_image = sub_image.to_image();
+        for (pos, cell) in console.iter() {
+            let dst = self.cell_rect(offset, pos)?;
 
-        for pix in sub_image.pixels_mut() {
-            if *pix == Rgb([255, 255, 255]) {
-                *pix = palette.fg.into();
+            if let Some(bg) = cell.bg {
+                fill_rect(canvas, bg, dst)?;
             }
+
+            if cell.glyph.is_blank() {
+                continue;
+            }
+
+            let (x, y) = cell.glyph.offset();
+
+            textures
+                .atlas
+                .set_color_mod(cell.fg.r, cell.fg.g, cell.fg.b);
+            self.copy_tile(canvas, &textures.atlas, (x.into(), y.into()), dst)?;
         }
 
-        let mut texture = canvas.create_texture_static(
-            PixelFormat::RGB24,
+        Ok(())
+    }
+
+    /// The area of the canvas covered by a cell position, for a grid whose top
+    /// left corner sits at the pixel `origin`.
+    fn cell_rect(
+        &self,
+        (origin_x, origin_y) : (i32, i32),
+        (x, y) : (i32, i32),
+    ) -> Result<Rect, PutGlyphError> {
+        Ok(Rect::new(
+            origin_x + x * i32::try_from(self.glyph_width)?,
+            origin_y + y * i32::try_from(self.glyph_height)?,
             self.glyph_width,
             self.glyph_height,
-        )?;
-
-        texture.update(None, sub_image.as_bytes(), 3 * self.glyph_width as usize)?;
-
-        let (x, y) = pos.into();
+        ))
+    }
 
+    /// Copies the tile at a tile sized offset of `texture` to an area of the
+    /// canvas.
+    fn copy_tile<T : RenderTarget>(
+        &self,
+        canvas : &mut Canvas<T>,
+        texture : &Texture,
+        (tile_x, tile_y) : (u32, u32),
+        dst : Rect,
+    ) -> Result<(), PutGlyphError> {
         canvas.copy(
-            &texture,
-            None,
+            texture,
             Rect::new(
-                x * i32::try_from(self.glyph_width)?,
-                y * i32::try_from(self.glyph_height)?,
+                i32::try_from(tile_x * self.glyph_width)?,
+                i32::try_from(tile_y * self.glyph_height)?,
                 self.glyph_width,
                 self.glyph_height,
             ),
+            dst,
         )?;
 
-        // We can do this safely, because by passing a refrence to a canvas into this
-        // function we ensure it lives at least the lifetime of this function.
-        unsafe { texture.destroy() };
-
         Ok(())
     }
 
+    /// Returns the textures uploaded to `canvas`, uploading the atlas if this
+    /// is the first time the font has been drawn onto it.
+    ///
+    /// Textures are kept for up to [`MAX_RENDERERS`] renderers, those of the
+    /// oldest being freed to make room for a new one.
+    fn textures_for<'c, T : RenderTarget>(
+        &self,
+        cache : &'c mut Vec<FontTextures>,
+        canvas : &Canvas<T>,
+    ) -> Result<&'c mut FontTextures, PutGlyphError> {
+        let renderer = RendererId::of(canvas);
+
+        if let Some(idx) = cache
+            .iter()
+            .position(|textures| textures.renderer == renderer)
+        {
+            return Ok(&mut cache[idx]);
+        }
+
+        // Textures of destroyed renderers were freed along with them
+        cache.retain(|textures| textures.renderer.is_alive());
+
+        if cache.len() >= MAX_RENDERERS {
+            cache.remove(0).destroy();
+        }
+
+        trace!("Uploading font atlas to renderer {:#x}", renderer.addr);
+
+        cache.push(FontTextures {
+            renderer,
+            atlas : upload_rgba(canvas, &atlas_to_rgba(&self.atlas))?,
+            extensions : HashMap::new(),
+        });
+
+        let idx = cache.len() - 1;
+        Ok(&mut cache[idx])
+    }
+
     /// Puts a [`str`] onto the screen by repeated calls to [`Self::put_char`].
     ///
     /// # Errors
@@ -286,6 +544,69 @@ impl Font {
     // }
 }
 
+impl Drop for Font {
+    fn drop(&mut self) {
+        for textures in self.cache.get_mut().drain(..) {
+            textures.destroy();
+        }
+    }
+}
+
+/// Converts a normalized atlas into white glyphs over a transparent
+/// background, so that the foreground can be applied with color modulation.
+fn atlas_to_rgba(atlas : &RgbImage) -> RgbaImage {
+    RgbaImage::from_fn(atlas.width(), atlas.height(), |x, y| {
+        Rgba([255, 255, 255, atlas.get_pixel(x, y).0[0]])
+    })
+}
+
+/// Converts an extension image into one with its white pixels cut out, to be
+/// drawn under [`extension_tint`].
+fn extension_to_rgba(image : &RgbImage) -> RgbaImage {
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let Rgb([r, g, b]) = *image.get_pixel(x, y);
+        Rgba([r, g, b, if [r, g, b] == [255, 255, 255] { 0 } else { 255 }])
+    })
+}
+
+/// The white pixels of an extension image over a transparent background, so
+/// that the foreground can be applied to them with color modulation.
+fn extension_tint(image : &RgbImage) -> RgbaImage {
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let white = image.get_pixel(x, y).0 == [255, 255, 255];
+        Rgba([255, 255, 255, if white { 255 } else { 0 }])
+    })
+}
+
+/// Fills an area of the canvas, leaving the canvas draw color as it was.
+fn fill_rect<T : RenderTarget>(
+    canvas : &mut Canvas<T>,
+    color : Color,
+    rect : Rect,
+) -> Result<(), PutGlyphError> {
+    let previous = canvas.draw_color();
+
+    canvas.set_draw_color(color);
+    let res = canvas.fill_rect(rect);
+    canvas.set_draw_color(previous);
+
+    Ok(res?)
+}
+
+/// Uploads an image to a static, alpha blended texture on `canvas`.
+fn upload_rgba<T : RenderTarget>(
+    canvas : &Canvas<T>,
+    image : &RgbaImage,
+) -> Result<Texture, PutGlyphError> {
+    let mut texture =
+        canvas.create_texture_static(PixelFormat::RGBA32, image.width(), image.height())?;
+
+    texture.update(None, image.as_bytes(), 4 * image.width() as usize)?;
+    texture.set_blend_mode(BlendMode::Blend);
+
+    Ok(texture)
+}
+
 pub enum FontKey {
     Char(Char437),
     Icon(String, String),
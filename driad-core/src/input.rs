@@ -0,0 +1,586 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::path::Path;
+use std::str::FromStr;
+use std::{fs, io};
+
+use mlua::Lua;
+use sdl3::event::{Event, WindowEvent};
+use sdl3::keyboard::{Keycode, Mod};
+use sdl3::mouse::MouseButton;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use toml::{de, ser};
+
+/// A set of modifier keys, left and right variants are treated the same.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+    pub const ALT : Self = Self(1 << 2);
+    pub const CTRL : Self = Self(1);
+    pub const GUI : Self = Self(1 << 3);
+    pub const NONE : Self = Self(0);
+    pub const SHIFT : Self = Self(1 << 1);
+
+    #[must_use]
+    pub const fn union(self, other : Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    #[must_use]
+    pub const fn contains(self, other : Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The modifiers held according to sdl, ignoring lock keys.
+    #[must_use]
+    pub fn from_mod(keymod : Mod) -> Self {
+        [
+            (Mod::LCTRLMOD | Mod::RCTRLMOD, Self::CTRL),
+            (Mod::LSHIFTMOD | Mod::RSHIFTMOD, Self::SHIFT),
+            (Mod::LALTMOD | Mod::RALTMOD, Self::ALT),
+            (Mod::LGUIMOD | Mod::RGUIMOD, Self::GUI),
+        ]
+        .into_iter()
+        .filter(|(flags, _)| keymod.intersects(*flags))
+        .fold(Self::NONE, |acc, (_, modifier)| acc.union(modifier))
+    }
+}
+
+/// The names modifiers are written with in bindings, in the order they are
+/// written.
+const MODIFIER_NAMES : [(&str, Modifiers); 4] = [
+    ("Ctrl", Modifiers::CTRL),
+    ("Shift", Modifiers::SHIFT),
+    ("Alt", Modifiers::ALT),
+    ("Gui", Modifiers::GUI),
+];
+
+/// The names mouse buttons are written with in bindings.
+const MOUSE_NAMES : [(&str, MouseButton); 5] = [
+    ("Mouse Left", MouseButton::Left),
+    ("Mouse Middle", MouseButton::Middle),
+    ("Mouse Right", MouseButton::Right),
+    ("Mouse X1", MouseButton::X1),
+    ("Mouse X2", MouseButton::X2),
+];
+
+/// A key or mouse button that can be bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Input {
+    Key(Keycode),
+    Mouse(MouseButton),
+}
+
+impl Display for Input {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "{}", key.name()),
+            Self::Mouse(button) => {
+                let name = MOUSE_NAMES
+                    .iter()
+                    .find(|(_, named)| named == button)
+                    .map_or("Mouse Unknown", |(name, _)| name);
+                write!(f, "{name}")
+            },
+        }
+    }
+}
+
+impl FromStr for Input {
+    type Err = ParseBindingError;
+
+    /// Parses an sdl key name such as `Up`, `Keypad 8` or `Q`, or a mouse
+    /// button such as `Mouse Left`.
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Some((_, button)) = MOUSE_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+        {
+            return Ok(Self::Mouse(*button));
+        }
+
+        Keycode::from_name(s)
+            .map(Self::Key)
+            .ok_or_else(|| ParseBindingError::UnknownInput(s.to_owned()))
+    }
+}
+
+/// An input along with the modifiers that have to be held for it to trigger,
+/// written as `Ctrl+Shift+S` or `Alt+Mouse Left`.
+///
+/// Modifiers have to match exactly, so `S` doesn't trigger while `Ctrl` is
+/// held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Binding {
+    pub modifiers : Modifiers,
+    pub input :     Input,
+}
+
+impl Binding {
+    #[must_use]
+    pub const fn new(input : Input, modifiers : Modifiers) -> Self {
+        Self { modifiers, input }
+    }
+
+    /// The binding a key or mouse button press would trigger, for capturing
+    /// new bindings from the player. `modifiers` is only used for mouse
+    /// presses, which don't carry their own.
+    #[must_use]
+    pub fn from_event(event : &Event, modifiers : Modifiers) -> Option<Self> {
+        match event {
+            Event::KeyDown {
+                keycode: Some(key),
+                keymod,
+                ..
+            } => Some(Self::new(Input::Key(*key), Modifiers::from_mod(*keymod))),
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                Some(Self::new(Input::Mouse(*mouse_btn), modifiers))
+            },
+            _ => None,
+        }
+    }
+}
+
+impl From<Input> for Binding {
+    fn from(input : Input) -> Self {
+        Self::new(input, Modifiers::NONE)
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f : &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in MODIFIER_NAMES {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        write!(f, "{}", self.input)
+    }
+}
+
+impl FromStr for Binding {
+    type Err = ParseBindingError;
+
+    fn from_str(s : &str) -> Result<Self, Self::Err> {
+        let mut rest = s.trim();
+        let mut modifiers = Modifiers::NONE;
+
+        // Keys can have a `+` in their name, so modifiers are stripped off the
+        // front rather than splitting on it.
+        'strip: loop {
+            for (name, modifier) in MODIFIER_NAMES {
+                let prefix = rest.get(.. name.len()).unwrap_or_default();
+                let after = rest.get(name.len() ..).unwrap_or_default();
+
+                if prefix.eq_ignore_ascii_case(name) && after.trim_start().starts_with('+') {
+                    modifiers = modifiers.union(modifier);
+                    rest = after.trim_start()[1 ..].trim_start();
+                    continue 'strip;
+                }
+            }
+
+            break;
+        }
+
+        Ok(Self::new(rest.parse()?, modifiers))
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = ParseBindingError;
+
+    fn try_from(value : String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Binding> for String {
+    fn from(value : Binding) -> Self {
+        value.to_string()
+    }
+}
+
+/// Named actions and the bindings that trigger them, stored as TOML with one
+/// key per action:
+///
+/// ```toml
+/// move_north = ["Up", "Keypad 8", "K"]
+/// open_inventory = ["I"]
+/// save = ["Ctrl+S"]
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Keybindings {
+    pub actions : BTreeMap<String, Vec<Binding>>,
+}
+
+impl Keybindings {
+    /// Reads keybindings from a TOML file.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be read, or isn't valid TOML of the right form.
+    pub fn load(path : impl AsRef<Path>) -> Result<Self, KeybindingsError> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the keybindings to a TOML file, so rebound keys persist.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be written.
+    pub fn save(&self, path : impl AsRef<Path>) -> Result<(), KeybindingsError> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// The bindings of an action.
+    #[must_use]
+    pub fn bindings(&self, action : &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The actions triggered by a binding.
+    pub fn actions_for(&self, binding : Binding) -> impl Iterator<Item = &str> {
+        self.actions
+            .iter()
+            .filter(move |(_, bindings)| bindings.contains(&binding))
+            .map(|(action, _)| action.as_str())
+    }
+
+    /// Adds a binding to an action, on top of those it already has.
+    pub fn bind(&mut self, action : impl Into<String>, binding : impl Into<Binding>) {
+        let binding = binding.into();
+        let bindings = self.actions.entry(action.into()).or_default();
+
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Removes a binding from an action, returning whether it was bound.
+    pub fn unbind(&mut self, action : &str, binding : Binding) -> bool {
+        let Some(bindings) = self.actions.get_mut(action) else {
+            return false;
+        };
+
+        let len = bindings.len();
+        bindings.retain(|bound| *bound != binding);
+        len != bindings.len()
+    }
+
+    /// Replaces every binding of an action.
+    pub fn rebind(
+        &mut self,
+        action : impl Into<String>,
+        bindings : impl IntoIterator<Item = Binding>,
+    ) {
+        self.actions
+            .insert(action.into(), bindings.into_iter().collect());
+    }
+}
+
+/// Tracks which actions are pressed, held and released from the events it is
+/// given.
+///
+/// Pressed and released actions last until [`Self::end_frame`], which
+/// [`Driad::run`](crate::Driad::run) calls after every frame.
+#[derive(Debug, Clone)]
+pub struct ActionMap {
+    pub bindings :   Keybindings,
+    /// Whether held keys repeating trigger their actions again.
+    pub key_repeat : bool,
+
+    modifiers : Modifiers,
+    /// The actions started by each input that is held down.
+    active :    HashMap<Input, Vec<String>>,
+    pressed :   HashSet<String>,
+    released :  HashSet<String>,
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        Self::new(Keybindings::default())
+    }
+}
+
+impl ActionMap {
+    #[must_use]
+    pub fn new(bindings : Keybindings) -> Self {
+        Self {
+            bindings,
+            key_repeat : true,
+            modifiers : Modifiers::NONE,
+            active : HashMap::new(),
+            pressed : HashSet::new(),
+            released : HashSet::new(),
+        }
+    }
+
+    /// Updates the actions from an event.
+    pub fn handle_event(&mut self, event : &Event) {
+        match event {
+            Event::KeyDown {
+                keycode: Some(key),
+                keymod,
+                repeat,
+                ..
+            } => {
+                self.modifiers = Modifiers::from_mod(*keymod);
+                self.press(Input::Key(*key), *repeat);
+            },
+            Event::KeyUp {
+                keycode: Some(key),
+                keymod,
+                ..
+            } => {
+                self.modifiers = Modifiers::from_mod(*keymod);
+                self.release(Input::Key(*key));
+            },
+            Event::MouseButtonDown { mouse_btn, .. } => self.press(Input::Mouse(*mouse_btn), false),
+            Event::MouseButtonUp { mouse_btn, .. } => self.release(Input::Mouse(*mouse_btn)),
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => {
+                // The key ups for anything held won't arrive while unfocused
+                let held : Vec<_> = self.active.keys().copied().collect();
+                for input in held {
+                    self.release(input);
+                }
+            },
+            _ => (),
+        }
+    }
+
+    fn press(&mut self, input : Input, repeat : bool) {
+        if repeat {
+            if self.key_repeat
+                && let Some(actions) = self.active.get(&input)
+            {
+                self.pressed.extend(actions.iter().cloned());
+            }
+            return;
+        }
+
+        let actions : Vec<String> = self
+            .bindings
+            .actions_for(Binding::new(input, self.modifiers))
+            .map(str::to_owned)
+            .collect();
+
+        self.pressed.extend(actions.iter().cloned());
+        self.active.entry(input).or_default().extend(actions);
+    }
+
+    fn release(&mut self, input : Input) {
+        let Some(actions) = self.active.remove(&input) else {
+            return;
+        };
+
+        for action in actions {
+            if !self.held(&action) {
+                self.released.insert(action);
+            }
+        }
+    }
+
+    /// Forgets which actions were pressed and released this frame.
+    pub fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+
+    /// Whether the action was triggered this frame, including by key repeat.
+    #[must_use]
+    pub fn pressed(&self, action : &str) -> bool {
+        self.pressed.contains(action)
+    }
+
+    /// Whether any input bound to the action is held down.
+    #[must_use]
+    pub fn held(&self, action : &str) -> bool {
+        self.active
+            .values()
+            .any(|actions| actions.iter().any(|held| held == action))
+    }
+
+    /// Whether the last input holding the action was let go this frame.
+    #[must_use]
+    pub fn released(&self, action : &str) -> bool {
+        self.released.contains(action)
+    }
+
+    /// The modifiers held as of the last key event.
+    #[must_use]
+    pub const fn modifiers(&self) -> Modifiers {
+        self.modifiers
+    }
+
+    /// Publishes the state of every action to lua as the global `input`
+    /// table, with `pressed`, `held` and `released` tables mapping action
+    /// names to `true`.
+    ///
+    /// # Errors
+    ///
+    /// If lua fails to allocate the tables.
+    pub fn publish(&self, lua : &Lua) -> Result<(), mlua::Error> {
+        let set = |actions : &mut dyn Iterator<Item = &String>| {
+            lua.create_table_from(actions.map(|action| (action.as_str(), true)))
+        };
+
+        let input = lua.create_table()?;
+        input.set("pressed", set(&mut self.pressed.iter())?)?;
+        input.set("held", set(&mut self.active.values().flatten())?)?;
+        input.set("released", set(&mut self.released.iter())?)?;
+
+        lua.globals().set("input", input)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ParseBindingError {
+    #[error("Unknown key or mouse button: {0:?}")]
+    UnknownInput(String),
+}
+
+#[derive(Debug, Error)]
+pub enum KeybindingsError {
+    #[error(transparent)]
+    TomlParseError(#[from] de::Error),
+
+    #[error(transparent)]
+    TomlWriteError(#[from] ser::Error),
+
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_parse_modifiers_and_mouse_buttons() -> Result<(), ParseBindingError> {
+        assert_eq!(
+            "alt + shift+mouse left".parse::<Binding>()?,
+            Binding::new(
+                Input::Mouse(MouseButton::Left),
+                Modifiers::ALT.union(Modifiers::SHIFT)
+            )
+        );
+        assert_eq!(
+            "Mouse X2".parse::<Binding>()?,
+            Binding::from(Input::Mouse(MouseButton::X2))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn bindings_parse_keys() -> Result<(), ParseBindingError> {
+        assert_eq!(
+            "Ctrl+S".parse::<Binding>()?,
+            Binding::new(Input::Key(Keycode::S), Modifiers::CTRL)
+        );
+        assert_eq!(
+            "Ctrl+Keypad +".parse::<Binding>()?,
+            Binding::new(Input::Key(Keycode::KpPlus), Modifiers::CTRL)
+        );
+        assert_eq!(
+            "Keypad 8".parse::<Binding>()?,
+            Binding::from(Input::Key(Keycode::Kp8))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_inputs_are_errors() {
+        assert!(matches!(
+            "Ctrl+Nope".parse::<Binding>(),
+            Err(ParseBindingError::UnknownInput(input)) if input == "Nope"
+        ));
+        assert!(matches!(
+            "Ctrl+".parse::<Binding>(),
+            Err(ParseBindingError::UnknownInput(_))
+        ));
+    }
+
+    #[test]
+    fn bindings_display_as_they_parse() -> Result<(), ParseBindingError> {
+        let binding = Binding::new(
+            Input::Mouse(MouseButton::Right),
+            Modifiers::GUI.union(Modifiers::CTRL),
+        );
+
+        assert_eq!(binding.to_string(), "Ctrl+Gui+Mouse Right");
+        assert_eq!(binding.to_string().parse::<Binding>()?, binding);
+
+        Ok(())
+    }
+
+    #[test]
+    fn keybindings_round_trip_through_toml() -> Result<(), KeybindingsError> {
+        let mut bindings = Keybindings::default();
+        bindings.bind("move_north", Input::Key(Keycode::Up));
+        bindings.bind("move_north", Input::Key(Keycode::Kp8));
+        bindings.bind(
+            "save",
+            Binding::new(Input::Key(Keycode::S), Modifiers::CTRL),
+        );
+        bindings.bind(
+            "select",
+            Binding::new(Input::Mouse(MouseButton::Left), Modifiers::SHIFT),
+        );
+
+        let text = toml::to_string(&bindings)?;
+
+        assert_eq!(
+            text,
+            "move_north = [\"Up\", \"Keypad 8\"]\nsave = [\"Ctrl+S\"]\nselect = [\"Shift+Mouse Left\"]\n"
+        );
+        assert_eq!(toml::from_str::<Keybindings>(&text)?, bindings);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bad_bindings_fail_to_load() {
+        assert!(matches!(
+            toml::from_str::<Keybindings>("save = [\"Ctrl+Nope\"]"),
+            Err(err) if err.message().contains("Nope")
+        ));
+    }
+
+    #[test]
+    fn bindings_are_added_once_and_removed() {
+        let save = Binding::new(Input::Mouse(MouseButton::Middle), Modifiers::CTRL);
+
+        let mut bindings = Keybindings::default();
+        bindings.bind("save", save);
+        bindings.bind("save", save);
+        bindings.bind("quick_save", save);
+
+        assert_eq!(bindings.bindings("save"), [save]);
+        assert_eq!(
+            bindings.actions_for(save).collect::<Vec<_>>(),
+            ["quick_save", "save"]
+        );
+
+        assert!(bindings.unbind("save", save));
+        assert!(!bindings.unbind("save", save));
+        assert!(bindings.bindings("save").is_empty());
+    }
+}
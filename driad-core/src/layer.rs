@@ -0,0 +1,97 @@
+use std::num::TryFromIntError;
+
+use sdl3::render::{Canvas, RenderTarget};
+
+use crate::console::Console;
+use crate::font::{Font, PutGlyphError};
+
+/// A [`Console`] drawn with its own [`Font`], so that grids of differently
+/// sized glyphs can be stacked on top of each other.
+pub struct Layer {
+    pub font :    Font,
+    pub console : Console,
+
+    /// The position of the top left cell of the layer in pixels.
+    pub offset :  (i32, i32),
+    pub visible : bool,
+}
+
+impl Layer {
+    /// Creates a visible layer of `width` by `height` cells.
+    #[must_use]
+    pub fn new(font : Font, width : i32, height : i32) -> Self {
+        Self {
+            font,
+            console : Console::new(width, height),
+            offset : (0, 0),
+            visible : true,
+        }
+    }
+
+    /// Creates a layer with enough cells to cover an area of `width` by
+    /// `height` pixels.
+    ///
+    /// # Errors
+    ///
+    /// If the number of cells doesn't fit into an [`i32`].
+    pub fn covering(font : Font, width : u32, height : u32) -> Result<Self, TryFromIntError> {
+        let cols = i32::try_from(width.div_ceil(font.glyph_width.max(1)))?;
+        let rows = i32::try_from(height.div_ceil(font.glyph_height.max(1)))?;
+
+        Ok(Self::new(font, cols, rows))
+    }
+
+    /// Resizes the layer to cover an area of `width` by `height` pixels,
+    /// returning whether its size changed. A resized layer starts out blank.
+    ///
+    /// # Errors
+    ///
+    /// If the number of cells doesn't fit into an [`i32`].
+    pub fn fit(&mut self, width : u32, height : u32) -> Result<bool, TryFromIntError> {
+        let cols = i32::try_from(width.div_ceil(self.font.glyph_width.max(1)))?;
+        let rows = i32::try_from(height.div_ceil(self.font.glyph_height.max(1)))?;
+
+        if (cols, rows) == (self.console.width(), self.console.height()) {
+            return Ok(false);
+        }
+
+        self.console = Console::new(cols, rows);
+        Ok(true)
+    }
+
+    /// The cell under a point given in canvas pixels, if it is inside the
+    /// layer.
+    #[must_use]
+    pub fn cell_at(&self, (x, y) : (f32, f32)) -> Option<(i32, i32)> {
+        let local = |pos : f32, offset : i32, glyph : u32| {
+            let cell = ((f64::from(pos) - f64::from(offset)) / f64::from(glyph.max(1))).floor();
+
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "Positions are clamped to the range of i32 first"
+            )]
+            let cell = cell.clamp(f64::from(i32::MIN), f64::from(i32::MAX)) as i32;
+            cell
+        };
+
+        let cell = (
+            local(x, self.offset.0, self.font.glyph_width),
+            local(y, self.offset.1, self.font.glyph_height),
+        );
+
+        self.console.rect().contains(cell).then_some(cell)
+    }
+
+    /// Draws the layer onto the canvas if it is visible.
+    ///
+    /// # Errors
+    ///
+    /// See [`Font::flush`].
+    pub fn draw<T : RenderTarget>(&self, canvas : &mut Canvas<T>) -> Result<(), PutGlyphError> {
+        if self.visible {
+            self.font.flush(canvas, &self.console, self.offset)
+        } else {
+            Ok(())
+        }
+    }
+}
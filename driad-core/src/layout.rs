@@ -0,0 +1,355 @@
+use crate::console::CellRect;
+
+/// How much room a segment of a [`Layout`] takes up along its direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Constraint {
+    /// Exactly this many cells.
+    Length(i32),
+    /// A percentage of the space being split.
+    Percentage(u16),
+    /// A fraction of the space being split, `Ratio(1, 3)` being a third.
+    Ratio(u32, u32),
+    /// At least this many cells, growing like `Fill(1)` into spare room.
+    Min(i32),
+    /// Grows like `Fill(1)` into spare room, but to no more than this many
+    /// cells.
+    Max(i32),
+    /// A share of the room left over by the other segments, in proportion to
+    /// the weights of every `Fill`.
+    Fill(u16),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Segments side by side, left to right.
+    #[default]
+    Horizontal,
+    /// Segments stacked top to bottom.
+    Vertical,
+}
+
+/// Space left empty around the edges of a rect.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Margin {
+    pub top :    i32,
+    pub right :  i32,
+    pub bottom : i32,
+    pub left :   i32,
+}
+
+impl Margin {
+    #[must_use]
+    pub const fn new(top : i32, right : i32, bottom : i32, left : i32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+
+    /// The same margin on every side.
+    #[must_use]
+    pub const fn uniform(margin : i32) -> Self {
+        Self::new(margin, margin, margin, margin)
+    }
+
+    /// `horizontal` on the left and right, `vertical` on the top and bottom.
+    #[must_use]
+    pub const fn symmetric(horizontal : i32, vertical : i32) -> Self {
+        Self::new(vertical, horizontal, vertical, horizontal)
+    }
+}
+
+impl CellRect {
+    /// The rect shrunk by a margin, never going below an empty rect.
+    #[must_use]
+    pub fn inset(&self, margin : Margin) -> Self {
+        Self::new(
+            self.x.saturating_add(margin.left),
+            self.y.saturating_add(margin.top),
+            self.width
+                .saturating_sub(margin.left)
+                .saturating_sub(margin.right)
+                .max(0),
+            self.height
+                .saturating_sub(margin.top)
+                .saturating_sub(margin.bottom)
+                .max(0),
+        )
+    }
+}
+
+/// Splits a rect into segments along one direction.
+///
+/// Fixed constraints are sized first, then whatever room is left is shared
+/// between `Min`, `Max` and `Fill` segments. When the constraints ask for more
+/// room than there is, the later segments are cut short.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Layout {
+    pub direction :   Direction,
+    pub constraints : Vec<Constraint>,
+    /// Space left around the rect before it is split.
+    pub margin :      Margin,
+    /// Empty cells between neighbouring segments.
+    pub spacing :     i32,
+}
+
+impl Layout {
+    #[must_use]
+    pub fn new(direction : Direction, constraints : impl IntoIterator<Item = Constraint>) -> Self {
+        Self {
+            direction,
+            constraints : constraints.into_iter().collect(),
+            margin : Margin::default(),
+            spacing : 0,
+        }
+    }
+
+    #[must_use]
+    pub fn horizontal(constraints : impl IntoIterator<Item = Constraint>) -> Self {
+        Self::new(Direction::Horizontal, constraints)
+    }
+
+    #[must_use]
+    pub fn vertical(constraints : impl IntoIterator<Item = Constraint>) -> Self {
+        Self::new(Direction::Vertical, constraints)
+    }
+
+    #[must_use]
+    pub const fn with_margin(mut self, margin : Margin) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_spacing(mut self, spacing : i32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Splits `rect` into one rect per constraint.
+    #[must_use]
+    pub fn split(&self, rect : impl Into<CellRect>) -> Vec<CellRect> {
+        let rect = rect.into().inset(self.margin);
+
+        let (start, length) = match self.direction {
+            Direction::Horizontal => (rect.x, rect.width),
+            Direction::Vertical => (rect.y, rect.height),
+        };
+
+        let gaps = i32::try_from(self.constraints.len().saturating_sub(1)).unwrap_or(i32::MAX);
+        let available = length
+            .saturating_sub(self.spacing.max(0).saturating_mul(gaps))
+            .max(0);
+
+        let end = start.saturating_add(length);
+        let mut pos = start;
+
+        self.sizes(available)
+            .into_iter()
+            .map(|size| {
+                let size = size.min(end.saturating_sub(pos)).max(0);
+                let segment = match self.direction {
+                    Direction::Horizontal => CellRect::new(pos, rect.y, size, rect.height),
+                    Direction::Vertical => CellRect::new(rect.x, pos, rect.width, size),
+                };
+
+                pos = pos
+                    .saturating_add(size)
+                    .saturating_add(self.spacing.max(0))
+                    .min(end);
+                segment
+            })
+            .collect()
+    }
+
+    /// The length of every segment when splitting `available` cells.
+    fn sizes(&self, available : i32) -> Vec<i32> {
+        let share = |numerator : i64, denominator : i64| {
+            i32::try_from(i64::from(available) * numerator / denominator.max(1)).unwrap_or(0)
+        };
+
+        let mut sizes = Vec::with_capacity(self.constraints.len());
+        let mut weights = Vec::with_capacity(self.constraints.len());
+
+        for constraint in &self.constraints {
+            let (size, weight) = match *constraint {
+                Constraint::Length(length) => (length.max(0), 0),
+                Constraint::Percentage(percent) => (share(i64::from(percent), 100), 0),
+                Constraint::Ratio(numerator, denominator) => {
+                    (share(i64::from(numerator), i64::from(denominator)), 0)
+                },
+                Constraint::Min(min) => (min.max(0), 1),
+                Constraint::Max(max) => (0, u32::from(max > 0)),
+                Constraint::Fill(weight) => (0, u32::from(weight)),
+            };
+
+            sizes.push(size);
+            weights.push(weight);
+        }
+
+        let fixed = sizes
+            .iter()
+            .fold(0, |sum : i32, size| sum.saturating_add(*size));
+        let mut remaining = available.saturating_sub(fixed);
+
+        // Each pass either caps a `Max` that would outgrow its limit, taking
+        // it out of the sharing, or shares out everything that is left.
+        while remaining > 0 {
+            let total = weights.iter().map(|weight| i64::from(*weight)).sum::<i64>();
+            if total == 0 {
+                break;
+            }
+
+            let portion = |weight : u32| {
+                i32::try_from(i64::from(remaining) * i64::from(weight) / total).unwrap_or(0)
+            };
+
+            let capped = self
+                .constraints
+                .iter()
+                .enumerate()
+                .find_map(|(idx, constraint)| {
+                    match *constraint {
+                        Constraint::Max(max)
+                            if weights[idx] > 0
+                                && sizes[idx].saturating_add(portion(weights[idx])) >= max =>
+                        {
+                            Some((idx, max))
+                        },
+                        _ => None,
+                    }
+                });
+
+            if let Some((idx, max)) = capped {
+                remaining = remaining.saturating_sub(max - sizes[idx]);
+                sizes[idx] = max;
+                weights[idx] = 0;
+                continue;
+            }
+
+            let mut given = 0;
+            for (size, weight) in sizes.iter_mut().zip(&weights) {
+                let portion = portion(*weight);
+                *size = size.saturating_add(portion);
+                given += portion;
+            }
+
+            // Cells lost to rounding go to the first growable segments
+            for (size, weight) in sizes.iter_mut().zip(&weights) {
+                if given == remaining {
+                    break;
+                }
+                if *weight > 0 {
+                    *size = size.saturating_add(1);
+                    given += 1;
+                }
+            }
+
+            break;
+        }
+
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widths(layout : &Layout, width : i32) -> Vec<(i32, i32)> {
+        layout
+            .split((0, 0, width, 1))
+            .into_iter()
+            .map(|rect| (rect.x, rect.width))
+            .collect()
+    }
+
+    #[test]
+    fn fixed_constraints_come_first() {
+        let layout = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(2),
+            Constraint::Percentage(50),
+        ]);
+
+        assert_eq!(widths(&layout, 10), [(0, 3), (3, 2), (5, 5)]);
+    }
+
+    #[test]
+    fn ratios_round_down() {
+        let layout = Layout::horizontal([Constraint::Ratio(1, 3); 3]);
+
+        assert_eq!(widths(&layout, 10), [(0, 3), (3, 3), (6, 3)]);
+    }
+
+    #[test]
+    fn fills_share_by_weight_and_keep_every_cell() {
+        let even = Layout::horizontal([Constraint::Fill(1); 3]);
+        let weighted = Layout::horizontal([Constraint::Fill(1), Constraint::Fill(3)]);
+
+        assert_eq!(widths(&even, 10), [(0, 4), (4, 3), (7, 3)]);
+        assert_eq!(widths(&weighted, 8), [(0, 2), (2, 6)]);
+    }
+
+    #[test]
+    fn min_grows_past_its_minimum() {
+        let layout = Layout::horizontal([Constraint::Min(4), Constraint::Fill(1)]);
+
+        assert_eq!(widths(&layout, 10), [(0, 7), (7, 3)]);
+        assert_eq!(widths(&layout, 3), [(0, 3), (3, 0)]);
+    }
+
+    #[test]
+    fn max_stops_growing_at_its_maximum() {
+        let layout =
+            Layout::horizontal([Constraint::Max(2), Constraint::Fill(1), Constraint::Max(0)]);
+
+        assert_eq!(widths(&layout, 10), [(0, 2), (2, 8), (10, 0)]);
+        assert_eq!(widths(&layout, 3), [(0, 2), (2, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn later_segments_are_cut_short() {
+        let layout = Layout::horizontal([Constraint::Length(6); 3]);
+
+        assert_eq!(widths(&layout, 10), [(0, 6), (6, 4), (10, 0)]);
+    }
+
+    #[test]
+    fn spacing_and_margin_are_left_empty() {
+        let layout = Layout::vertical([Constraint::Fill(1); 2])
+            .with_spacing(2)
+            .with_margin(Margin::symmetric(1, 2));
+
+        assert_eq!(
+            layout.split((0, 0, 10, 14)),
+            [CellRect::new(1, 2, 8, 4), CellRect::new(1, 8, 8, 4)]
+        );
+    }
+
+    #[test]
+    fn huge_constraints_dont_overflow() {
+        let layout = Layout::horizontal([
+            Constraint::Length(i32::MAX),
+            Constraint::Fill(1),
+            Constraint::Min(i32::MAX),
+        ])
+        .with_spacing(i32::MAX);
+
+        assert_eq!(
+            layout.split((5, 0, 10, 1)),
+            [
+                CellRect::new(5, 0, 10, 1),
+                CellRect::new(15, 0, 0, 1),
+                CellRect::new(15, 0, 0, 1)
+            ]
+        );
+        assert!(
+            CellRect::new(i32::MAX - 1, 0, 4, 4)
+                .inset(Margin::uniform(i32::MAX))
+                .is_empty()
+        );
+    }
+}
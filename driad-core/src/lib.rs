@@ -1,30 +1,85 @@
-use std::path::Path;
+use std::num::TryFromIntError;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, mem};
 
-use log::{error, trace, warn};
+use image::{ImageFormat, RgbImage};
+use log::{error, info, trace, warn};
 use mlua::Lua;
-use sdl3::render::Canvas;
+use sdl3::event::{Event, WindowEvent};
+use sdl3::keyboard::Keycode;
+use sdl3::pixels::Color as SdlColor;
+use sdl3::rect::Rect;
+use sdl3::render::{BlendMode, Canvas};
 use sdl3::video::{Window, WindowBuildError};
-use sdl3::{EventPump, IntegerOrSdlError, Sdl, VideoSubsystem};
+use sdl3::{EventPump, IntegerOrSdlError, Sdl, VideoSubsystem, hint};
 use thiserror::Error;
 
-use crate::font::{Font, FontCreationError};
+use crate::app::{FrameTimes, LoopSettings};
+use crate::color::Color;
+use crate::draw::{DrawItem, DrawPass};
+use crate::font::{Font, FontCreationError, PutGlyphError};
+use crate::input::{ActionMap, Modifiers};
+use crate::layer::Layer;
+use crate::mouse::{Mouse, MouseEvent};
 use crate::plugin::{LoadPluginError, Plugin, PluginApi};
+use crate::recorder::{Recorder, RecorderSettings, Recording};
+use crate::snapshot::SnapshotError;
+use crate::widget::{Ui, UiEvent};
 
+pub mod app;
+pub mod border;
 pub mod char;
 pub mod color;
+pub mod console;
+pub mod draw;
 pub mod font;
+pub mod input;
+pub mod layer;
+pub mod layout;
+pub mod markup;
+pub mod mouse;
 pub mod plugin;
+pub mod recorder;
+pub mod shape;
+pub mod snapshot;
+pub mod text;
 pub mod widget;
 
 pub struct Driad {
     /// The Sdl Library
-    pub sdl :    Sdl,
-    pub video :  VideoSubsystem,
-    pub window : Window,
-    pub canvas : Canvas<Window>,
-    pub font :   Font,
+    pub sdl :              Sdl,
+    pub video :            VideoSubsystem,
+    pub window :           Window,
+    pub canvas :           Canvas<Window>,
+    /// The layers drawn each frame, from the bottom up. The first layer is the
+    /// base layer sized to the window and is always present.
+    layers :               Vec<Layer>,
+    /// The color the canvas is cleared to before the layers are drawn.
+    pub clear_color :      Color,
+    /// Pressing this key saves a screenshot and a text dump of the layers into
+    /// `screenshot_dir` when the frame is next presented, unless a widget
+    /// taking text has focus.
+    pub screenshot_key :   Option<Keycode>,
+    pub screenshot_dir :   PathBuf,
+    screenshot_requested : bool,
+    /// Captures presented frames while a recording is running.
+    pub recorder :         Option<Recorder>,
+    /// Commands submitted for the next frame, drawn and emptied by
+    /// [`Self::render`].
+    pub draw_queue :       Vec<DrawItem>,
+    /// Timing of the loop run by [`Self::run`].
+    pub loop_settings :    LoopSettings,
+    /// Measurements of the frames run by [`Self::run`].
+    pub frame_times :      FrameTimes,
 
     pub event_pump : EventPump,
+    /// The actions triggered by the events passed to [`Self::handle_event`].
+    pub input :      ActionMap,
+    /// The position of the mouse, updated by [`Self::handle_event`].
+    pub mouse :      Mouse,
+    /// The widgets drawn over every layer.
+    pub ui :         Ui,
 
     pub plugins_initialized : bool,
     /// The Lua runtime
@@ -34,12 +89,24 @@ pub struct Driad {
 
 // TODO, rework this to be a window builder
 #[derive(Debug)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "Each flag is an independent window option"
+)]
 pub struct WindowProperties {
     pub width :      u32,
     pub height :     u32,
     pub name :       &'static str,
     pub centered :   bool,
     pub borderless : bool,
+    /// Lets the user resize the window, the layers growing or shrinking to
+    /// cover it.
+    pub resizable :  bool,
+    /// Renders into a hidden window on sdl's dummy video driver with the
+    /// software renderer, for use on machines without a display or GPU.
+    pub headless :   bool,
+    /// Waits for the display's vertical refresh when presenting.
+    pub vsync :      bool,
 }
 
 impl Default for WindowProperties {
@@ -50,6 +117,9 @@ impl Default for WindowProperties {
             name :       "Driad Window",
             centered :   true,
             borderless : false,
+            resizable :  false,
+            headless :   false,
+            vsync :      false,
         }
     }
 }
@@ -66,6 +136,16 @@ impl Driad {
         font : Font,
         plugin_paths : &[T],
     ) -> Result<Self, DriadNewError> {
+        if window_properties.headless {
+            trace!("Using the headless video and render drivers");
+            hint::set("SDL_VIDEO_DRIVER", "dummy");
+            hint::set("SDL_RENDER_DRIVER", "software");
+        }
+
+        if window_properties.vsync {
+            hint::set("SDL_RENDER_VSYNC", "1");
+        }
+
         trace!("Initializing SDL 3");
         let sdl = sdl3::init()?;
 
@@ -78,9 +158,15 @@ impl Driad {
         if window_properties.borderless {
             window.borderless();
         }
+        if window_properties.resizable {
+            window.resizable();
+        }
         if window_properties.centered {
             window.position_centered();
         }
+        if window_properties.headless {
+            window.hidden();
+        }
 
         let mut window = window.build()?;
 
@@ -91,9 +177,21 @@ impl Driad {
             window_properties.height * font.glyph_height,
         )?;
 
+        let ui = Ui::new(
+            i32::try_from(window_properties.width)?,
+            i32::try_from(window_properties.height)?,
+        );
+
+        let base = Layer::new(
+            font,
+            i32::try_from(window_properties.width)?,
+            i32::try_from(window_properties.height)?,
+        );
+
         let event_pump = sdl.event_pump()?;
 
         let lua = Lua::new();
+        plugin::register_dialogs(&lua)?;
 
         let plugins = plugin_paths
             .iter()
@@ -115,8 +213,25 @@ impl Driad {
             video,
             window,
             canvas,
-            font,
+            layers : vec![base],
+            clear_color : Color::new(0, 0, 0),
+            draw_queue : Vec::new(),
+            // Sleeping on top of vsync would drop frames on faster displays
+            loop_settings : LoopSettings {
+                max_fps : LoopSettings::default()
+                    .max_fps
+                    .filter(|_| !window_properties.vsync),
+                ..LoopSettings::default()
+            },
+            frame_times : FrameTimes::default(),
+            screenshot_key : Some(Keycode::F12),
+            screenshot_dir : PathBuf::from("screenshots"),
+            screenshot_requested : false,
+            recorder : None,
             event_pump,
+            input : ActionMap::default(),
+            mouse : Mouse::default(),
+            ui,
             lua,
             plugins,
             plugins_initialized : false,
@@ -164,6 +279,379 @@ impl Driad {
         }
     }
 
+    /// Pushes a new layer on top of the others, sized to cover the whole
+    /// window with the glyphs of `font`. Returns the index of the layer.
+    ///
+    /// # Errors
+    ///
+    /// If the number of cells doesn't fit into an [`i32`].
+    pub fn add_layer(&mut self, font : Font) -> Result<usize, TryFromIntError> {
+        let (width, height) = self.window.size_in_pixels();
+
+        self.layers.push(Layer::covering(font, width, height)?);
+
+        Ok(self.layers.len() - 1)
+    }
+
+    /// Removes a layer, moving the layers above it down by one. The base layer
+    /// can't be removed.
+    pub fn remove_layer(&mut self, idx : usize) -> Option<Layer> {
+        (idx > 0 && idx < self.layers.len()).then(|| self.layers.remove(idx))
+    }
+
+    /// The layers, from the bottom up. There is always at least the base
+    /// layer.
+    #[must_use]
+    #[inline]
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    #[must_use]
+    #[inline]
+    pub fn layers_mut(&mut self) -> &mut [Layer] {
+        &mut self.layers
+    }
+
+    /// Resizes every layer to cover `width` by `height` pixels. The UI follows
+    /// the base layer the next time it is rendered.
+    fn fit_layers(&mut self, width : i32, height : i32) {
+        let width = u32::try_from(width).unwrap_or(0);
+        let height = u32::try_from(height).unwrap_or(0);
+
+        for layer in &mut self.layers {
+            if let Err(err) = layer.fit(width, height) {
+                warn!("Failed to resize a layer: {err}");
+            }
+        }
+    }
+
+    /// The layer at the bottom of the stack.
+    #[must_use]
+    #[inline]
+    pub fn base(&self) -> &Layer {
+        &self.layers[0]
+    }
+
+    /// The layer at the bottom of the stack.
+    #[must_use]
+    #[inline]
+    pub fn base_mut(&mut self) -> &mut Layer {
+        &mut self.layers[0]
+    }
+
+    /// Queues a [`DrawPass`] to be drawn on the next call to [`Self::render`].
+    #[inline]
+    pub fn submit(&mut self, pass : impl Into<DrawPass>) {
+        self.draw_queue.extend(pass.into());
+    }
+
+    /// Publishes the state of [`Self::input`] and [`Self::mouse`] to lua, then
+    /// calls the draw pass of every plugin and submits the results. Plugins
+    /// that fail are logged and skipped. Dialogs opened from lua are added to
+    /// [`Self::ui`].
+    pub fn submit_plugin_draws(&mut self) {
+        if let Err(err) = self
+            .input
+            .publish(&self.lua)
+            .and_then(|()| self.mouse.publish(&self.lua))
+        {
+            warn!("Failed to publish input to lua: {err}");
+        }
+
+        for plugin in &self.plugins {
+            match plugin.draw_pass() {
+                Some(Ok(pass)) => self.draw_queue.extend(pass),
+                Some(Err(err)) => warn!("{}: {err}", plugin.metadata.name),
+                None => (),
+            }
+        }
+
+        for dialog in plugin::take_dialogs(&self.lua) {
+            self.ui.open_dialog(dialog);
+        }
+    }
+
+    /// Clears the canvas and draws every visible layer onto it from the bottom
+    /// up, each followed by the queued commands targeting it in `z` order.
+    /// Cells with a transparent background let the layers below show through.
+    /// [`Self::ui`] is drawn last, over everything else, with the font of the
+    /// base layer. The layers are darkened first while a dialog dims its
+    /// backdrop.
+    ///
+    /// Commands that fail to draw are logged and skipped, the queue is empty
+    /// afterwards.
+    ///
+    /// # Errors
+    ///
+    /// See [`Font::flush`].
+    pub fn render(&mut self) -> Result<(), PutGlyphError> {
+        self.canvas.set_draw_color(self.clear_color);
+        self.canvas.clear();
+
+        let mut queue = mem::take(&mut self.draw_queue);
+        queue.sort_by_key(|item| (item.layer, item.z));
+
+        let mut queue = queue.into_iter().peekable();
+
+        for (idx, layer) in self.layers.iter().enumerate() {
+            layer.draw(&mut self.canvas)?;
+
+            let (width, height) = self.canvas.output_size()?;
+            self.canvas
+                .set_viewport(Rect::new(layer.offset.0, layer.offset.1, width, height));
+
+            while let Some(item) = queue.next_if(|item| item.layer == idx) {
+                if !layer.visible {
+                    continue;
+                }
+
+                if let Err(err) = item.command.execute(&layer.font, &mut self.canvas) {
+                    warn!("{err}");
+                }
+            }
+
+            self.canvas.set_viewport(None);
+        }
+
+        for item in queue {
+            warn!("Dropped a draw command for missing layer {}", item.layer);
+        }
+
+        // The layers are dimmed here and the widgets by the ui, once each
+        if self.ui.dims_backdrop() {
+            let blend_mode = self.canvas.blend_mode();
+
+            self.canvas.set_blend_mode(BlendMode::Blend);
+            self.canvas.set_draw_color(SdlColor::RGBA(0, 0, 0, 128));
+            self.canvas.fill_rect(None)?;
+            self.canvas.set_blend_mode(blend_mode);
+        }
+
+        let base = &self.layers[0];
+        self.ui.resize(base.console.width(), base.console.height());
+        self.ui.draw(&mut self.canvas, &base.font, base.offset)?;
+
+        // Focus may have moved outside of an event, such as by the host
+        self.sync_text_input();
+
+        Ok(())
+    }
+
+    /// Reads back the frame drawn by [`Self::render`]. This must happen before
+    /// the canvas is presented.
+    ///
+    /// # Errors
+    ///
+    /// See [`snapshot::read_canvas`].
+    pub fn read_frame(&self) -> Result<RgbImage, SnapshotError> {
+        snapshot::read_canvas(&self.canvas)
+    }
+
+    /// Translates a mouse event from window coordinates into canvas pixels
+    /// and the cells of every layer. Returns `None` for other events.
+    #[must_use]
+    pub fn mouse_event(&self, event : &Event) -> Option<MouseEvent> {
+        let (kind, (x, y)) = mouse::event_kind(event)?;
+
+        // Window coordinates differ from pixels on high density displays
+        let (window_width, window_height) = self.window.size();
+        let (pixel_width, pixel_height) = self
+            .canvas
+            .output_size()
+            .unwrap_or_else(|_| self.window.size());
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "Window sizes are far below where f32 loses precision"
+        )]
+        let scale = |pixels : u32, points : u32| pixels as f32 / points.max(1) as f32;
+
+        let pixel = (
+            x * scale(pixel_width, window_width),
+            y * scale(pixel_height, window_height),
+        );
+
+        Some(MouseEvent {
+            kind,
+            pixel,
+            cells : self
+                .layers
+                .iter()
+                .map(|layer| layer.cell_at(pixel))
+                .collect(),
+        })
+    }
+
+    /// Lets driad react to an event before the host does, returning whether
+    /// the event was consumed, either by the screenshot key or by a widget of
+    /// [`Self::ui`].
+    ///
+    /// Every event updates [`Self::mouse`] and mouse events are passed on to
+    /// the plugins. When the window changes size the layers are resized to
+    /// cover it. [`Self::input`] sees every event except presses consumed
+    /// by the UI, so typing into a widget doesn't also trigger actions.
+    pub fn handle_event(&mut self, event : &Event) -> bool {
+        let mouse = self.mouse_event(event);
+
+        if let Some(mouse) = &mouse {
+            self.mouse.update(mouse);
+
+            for plugin in &self.plugins {
+                if let Some(Err(err)) = plugin.mouse_event(mouse) {
+                    warn!("{}: {err}", plugin.metadata.name);
+                }
+            }
+        }
+
+        if let Event::Window {
+            win_event: WindowEvent::PixelSizeChanged(width, height),
+            ..
+        } = *event
+        {
+            self.fit_layers(width, height);
+        }
+
+        if let Event::KeyDown {
+            keycode: Some(key),
+            repeat: false,
+            ..
+        } = event
+            && Some(*key) == self.screenshot_key
+            && !self.ui.wants_text_input()
+        {
+            self.screenshot_requested = true;
+            return true;
+        }
+
+        let ui_event = match UiEvent::from_event(event, mouse.as_ref()) {
+            Some(UiEvent::Key { key, modifiers, .. })
+                if self.ui.wants_text_input()
+                    && matches!(
+                        (key, modifiers),
+                        (Keycode::V, Modifiers::CTRL) | (Keycode::Insert, Modifiers::SHIFT)
+                    ) =>
+            {
+                self.video
+                    .clipboard()
+                    .clipboard_text()
+                    .ok()
+                    .map(UiEvent::Paste)
+            },
+            ui_event => ui_event,
+        };
+
+        let consumed = ui_event
+            .as_ref()
+            .is_some_and(|ui_event| self.ui.handle_event(ui_event));
+
+        if !consumed || !ui_event.as_ref().is_some_and(UiEvent::is_press) {
+            self.input.handle_event(event);
+        }
+
+        self.sync_text_input();
+
+        consumed
+    }
+
+    /// Starts sdl's text input while the focused widget wants text, and stops
+    /// it otherwise.
+    pub fn sync_text_input(&self) {
+        let text_input = self.video.text_input();
+        let wanted = self.ui.wants_text_input();
+
+        if wanted != text_input.is_active(&self.window) {
+            if wanted {
+                text_input.start(&self.window);
+            } else {
+                text_input.stop(&self.window);
+            }
+        }
+    }
+
+    /// Presents the frame drawn by [`Self::render`] to the window, saving a
+    /// screenshot first if the screenshot key was pressed.
+    pub fn present(&mut self) {
+        if mem::take(&mut self.screenshot_requested) {
+            let millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis();
+            let path = self.screenshot_dir.join(format!("driad-{millis}"));
+
+            if let Err(err) = fs::create_dir_all(&self.screenshot_dir)
+                .map_err(SnapshotError::from)
+                .and_then(|()| self.screenshot(path.with_extension("png")))
+                .and_then(|()| self.screenshot_text(path.with_extension("txt")))
+            {
+                warn!("Failed to save screenshot: {err}");
+            } else {
+                info!("Saved screenshot to {}", path.display());
+            }
+        }
+
+        if let Some(recorder) = &mut self.recorder
+            && recorder.wants_frame()
+        {
+            match snapshot::read_canvas(&self.canvas) {
+                Ok(frame) => recorder.push(frame),
+                Err(err) => warn!("Failed to capture a frame: {err}"),
+            }
+        }
+
+        self.canvas.present();
+    }
+
+    /// Starts capturing presented frames, discarding any recording already in
+    /// progress.
+    pub fn start_recording(&mut self, settings : RecorderSettings) {
+        trace!("Starting a recording");
+        self.recorder = Some(Recorder::new(settings));
+    }
+
+    /// Stops capturing frames and returns what was recorded, if a recording
+    /// was running.
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recorder.take().map(Recorder::finish)
+    }
+
+    #[must_use]
+    #[inline]
+    pub const fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Saves the frame drawn by [`Self::render`] as a PNG. This must happen
+    /// before the canvas is presented.
+    ///
+    /// # Errors
+    ///
+    /// If the frame can't be read back or the file can't be written.
+    pub fn screenshot(&self, path : impl AsRef<Path>) -> Result<(), SnapshotError> {
+        self.read_frame()?
+            .save_with_format(path, ImageFormat::Png)
+            .map_err(From::from)
+    }
+
+    /// Saves the glyphs of every visible layer as UTF-8 text, from the bottom
+    /// layer up, followed by those of the UI.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be written.
+    pub fn screenshot_text(&self, path : impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let text = self
+            .layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| layer.visible)
+            .map(|(idx, layer)| format!("Layer {idx}:\n{}\n", layer.console.to_text()))
+            .chain([format!("UI:\n{}\n", self.ui.console().to_text())])
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        fs::write(path, text).map_err(From::from)
+    }
+
     #[must_use]
     #[inline]
     pub const fn sdl(&self) -> &Sdl {
@@ -182,46 +670,15 @@ pub enum DriadNewError {
     #[error(transparent)]
     IntegerOrSdlError(#[from] IntegerOrSdlError),
 
+    #[error(transparent)]
+    TryFromIntError(#[from] TryFromIntError),
+
     #[error(transparent)]
     FontCreationError(#[from] FontCreationError),
 
     #[error(transparent)]
     LoadPluginError(#[from] LoadPluginError),
-}
-
-// TODO
-pub mod draw {
-    use std::ops::{Deref, DerefMut};
-
-    use crate::char::Char437;
-    use crate::color::Color;
-
-    #[derive(Debug, Default)]
-    pub enum DrawCommand {
-        PutChr {
-            pos : (i32, i32),
-            chr : Char437,
-            col : Color,
-        },
-        #[default]
-        None,
-    }
 
-    pub struct DrawPass {
-        commands : Vec<DrawCommand>,
-    }
-
-    impl DerefMut for DrawPass {
-        fn deref_mut(&mut self) -> &mut Self::Target {
-            &mut self.commands
-        }
-    }
-
-    impl Deref for DrawPass {
-        type Target = Vec<DrawCommand>;
-
-        fn deref(&self) -> &Self::Target {
-            &self.commands
-        }
-    }
+    #[error(transparent)]
+    LuaError(#[from] mlua::Error),
 }
@@ -0,0 +1,331 @@
+use sdl3::render::{Canvas, RenderTarget};
+use thiserror::Error;
+
+use crate::char::{CP437CharExt, Char437};
+use crate::color::{Color, Palette, ParseColorError};
+use crate::console::Console;
+use crate::font::{Font, PutGlyphError};
+
+/// A run of text drawn with a single palette.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text :    String,
+    pub palette : Palette,
+}
+
+impl Span {
+    #[must_use]
+    pub fn new(text : impl Into<String>, palette : impl Into<Palette>) -> Self {
+        Self {
+            text :    text.into(),
+            palette : palette.into(),
+        }
+    }
+}
+
+/// Which part of the palette a tag changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tag {
+    Fg,
+    Bg,
+}
+
+/// Parses text containing color tags into spans, starting from `base`.
+///
+/// - `[fg=red]` and `[bg=red]` change the foreground or background until the
+///   matching `[/fg]` or `[/bg]`, `[/]` closes whichever tag was opened last.
+/// - Colors are given as `#rgb`, `#rrggbb`, one of the
+///   [`NAMED_COLORS`](crate::color::NAMED_COLORS), or `accent1`/`accent2` for
+///   the accents of `base`. `[bg=none]` leaves the background transparent.
+/// - `[[` is a literal `[`.
+///
+/// Tags still open at the end of the text are closed implicitly.
+///
+/// # Errors
+///
+/// If a tag is unknown, unterminated, has an invalid color, or closes a tag
+/// that isn't open.
+pub fn parse(text : &str, base : impl Into<Palette>) -> Result<Vec<Span>, MarkupError> {
+    let base = base.into();
+
+    let mut spans : Vec<Span> = Vec::new();
+    let mut stack : Vec<(Tag, Palette)> = Vec::new();
+    let mut rest = text;
+
+    while let Some(open) = rest.find('[') {
+        let palette = stack.last().map_or(base, |(_, palette)| *palette);
+        push_text(&mut spans, &rest[.. open], palette);
+        rest = &rest[open + 1 ..];
+
+        if let Some(after) = rest.strip_prefix('[') {
+            push_text(&mut spans, "[", palette);
+            rest = after;
+            continue;
+        }
+
+        let close = rest
+            .find(']')
+            .ok_or_else(|| MarkupError::Unterminated(rest.to_owned()))?;
+        let tag = &rest[.. close];
+        rest = &rest[close + 1 ..];
+
+        if let Some((name, value)) = tag.split_once('=') {
+            let (tag, color) = (tag_from_name(name)?, value.trim());
+            let mut palette = palette;
+
+            match (tag, color) {
+                (Tag::Fg, "accent1") => palette.fg = base.fg_accent1(),
+                (Tag::Fg, "accent2") => palette.fg = base.fg_accent2(),
+                (Tag::Bg, "accent1") => palette.bg = base.bg_accent1(),
+                (Tag::Bg, "accent2") => palette.bg = base.bg_accent2(),
+                (Tag::Fg, color) => palette.fg = color.parse::<Color>()?,
+                (Tag::Bg, "none") => {},
+                (Tag::Bg, color) => palette.bg = color.parse::<Color>()?,
+            }
+
+            if tag == Tag::Bg {
+                palette.transparent = color == "none";
+            }
+
+            stack.push((tag, palette));
+        } else {
+            let name = tag
+                .strip_prefix('/')
+                .ok_or_else(|| MarkupError::UnknownTag(tag.to_owned()))?;
+
+            let expected = match name {
+                "" => None,
+                name => Some(tag_from_name(name)?),
+            };
+
+            match stack.pop() {
+                Some((open, _)) if expected.is_none_or(|expected| expected == open) => {},
+                _ => return Err(MarkupError::UnexpectedClose(tag.to_owned())),
+            }
+        }
+    }
+
+    let palette = stack.last().map_or(base, |(_, palette)| *palette);
+    push_text(&mut spans, rest, palette);
+
+    Ok(spans)
+}
+
+fn tag_from_name(name : &str) -> Result<Tag, MarkupError> {
+    match name.trim() {
+        "fg" => Ok(Tag::Fg),
+        "bg" => Ok(Tag::Bg),
+        name => Err(MarkupError::UnknownTag(name.to_owned())),
+    }
+}
+
+/// Appends text to the last span if it has the same palette, starting a new
+/// span otherwise.
+fn push_text(spans : &mut Vec<Span>, text : &str, palette : Palette) {
+    if text.is_empty() {
+        return;
+    }
+
+    match spans.last_mut() {
+        Some(span) if span.palette == palette => span.text.push_str(text),
+        _ => spans.push(Span::new(text, palette)),
+    }
+}
+
+/// Every glyph of `spans` along with the palette it is drawn with. Chars that
+/// don't exist in codepage 437 become `?`.
+pub fn glyphs(spans : &[Span]) -> impl Iterator<Item = (Char437, Palette)> {
+    spans.iter().flat_map(|span| {
+        span.text
+            .chars()
+            .map(|chr| (chr.to_cp437().unwrap_or(Char437(b'?')), span.palette))
+    })
+}
+
+impl Console {
+    /// Writes styled spans left to right starting at `pos`, returning the
+    /// column just past the last glyph.
+    pub fn print_spans(&mut self, pos : impl Into<(i32, i32)>, spans : &[Span]) -> i32 {
+        let (x, y) = pos.into();
+        let mut col = x;
+
+        for (glyph, palette) in glyphs(spans) {
+            self.set((col, y), glyph, palette);
+            col += 1;
+        }
+
+        col
+    }
+
+    /// Parses `text` with [`parse`] and writes it starting at `pos`, returning
+    /// the column just past the last glyph.
+    ///
+    /// # Errors
+    ///
+    /// See [`parse`].
+    pub fn print_markup(
+        &mut self,
+        pos : impl Into<(i32, i32)>,
+        text : &str,
+        palette : impl Into<Palette>,
+    ) -> Result<i32, MarkupError> {
+        Ok(self.print_spans(pos, &parse(text, palette)?))
+    }
+}
+
+impl Font {
+    /// Draws styled spans left to right starting at `pos`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Font::put`].
+    pub fn put_spans<T : RenderTarget>(
+        &self,
+        canvas : &mut Canvas<T>,
+        spans : &[Span],
+        pos : impl Into<(i32, i32)>,
+    ) -> Result<(), PutGlyphError> {
+        let (x, y) = pos.into();
+
+        (x ..)
+            .zip(glyphs(spans))
+            .try_for_each(|(col, (glyph, palette))| {
+                self.put_char437(canvas, glyph, (col, y), palette)
+            })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MarkupError {
+    #[error("Unknown markup tag: [{0}]")]
+    UnknownTag(String),
+
+    #[error("Markup tag is never closed with ]: [{0}")]
+    Unterminated(String),
+
+    #[error("Closing tag without a matching opening tag: [{0}]")]
+    UnexpectedClose(String),
+
+    #[error(transparent)]
+    ParseColorError(#[from] ParseColorError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED : Color = Color::new(170, 0, 0);
+    const BLUE : Color = Color::new(0, 0, 255);
+
+    #[test]
+    fn tags_change_the_palette_until_closed() -> Result<(), MarkupError> {
+        let base = Palette::default();
+        let red = Palette { fg : RED, ..base };
+        let red_on_blue = Palette { bg : BLUE, ..red };
+
+        assert_eq!(
+            parse("a[fg=red]b[bg=#00f]c[/]d[/fg]e", base)?,
+            [
+                Span::new("a", base),
+                Span::new("b", red),
+                Span::new("c", red_on_blue),
+                Span::new("d", red),
+                Span::new("e", base),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn open_tags_close_at_the_end() -> Result<(), MarkupError> {
+        let base = Palette::default();
+
+        assert_eq!(
+            parse("[fg=red]a", base)?,
+            [Span::new("a", Palette { fg : RED, ..base })]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn double_brackets_are_literal() -> Result<(), MarkupError> {
+        let base = Palette::default();
+
+        assert_eq!(parse("a [[b] c", base)?, [Span::new("a [b] c", base)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bg_none_is_transparent() -> Result<(), MarkupError> {
+        let base = Palette::default();
+
+        assert_eq!(
+            parse("[bg=red]a[bg=none]b", base)?,
+            [
+                Span::new("a", Palette { bg : RED, ..base }),
+                Span::new(
+                    "b",
+                    Palette {
+                        bg : RED,
+                        transparent : true,
+                        ..base
+                    }
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn accents_come_from_the_base() -> Result<(), MarkupError> {
+        let base = Palette::default().with_accent1(RED, BLUE);
+
+        assert_eq!(
+            parse("[fg=accent1][bg=accent2]a", base)?,
+            [Span::new(
+                "a",
+                Palette {
+                    fg : RED,
+                    bg : BLUE,
+                    ..base
+                }
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn bad_tags_are_errors() {
+        let base = Palette::default();
+
+        assert!(matches!(
+            parse("[bold]a", base),
+            Err(MarkupError::UnknownTag(_))
+        ));
+        assert!(matches!(
+            parse("[size=2]a", base),
+            Err(MarkupError::UnknownTag(_))
+        ));
+        assert!(matches!(
+            parse("[fg=red", base),
+            Err(MarkupError::Unterminated(_))
+        ));
+        assert!(matches!(
+            parse("a[/]", base),
+            Err(MarkupError::UnexpectedClose(_))
+        ));
+        assert!(matches!(
+            parse("[fg=red]a[/bg]", base),
+            Err(MarkupError::UnexpectedClose(_))
+        ));
+        assert!(matches!(
+            parse("[fg=nope]a", base),
+            Err(MarkupError::ParseColorError(_))
+        ));
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashSet;
+
+use mlua::{IntoLua, Lua, Table, Value};
+use sdl3::event::{Event, WindowEvent};
+use sdl3::mouse::{MouseButton, MouseWheelDirection};
+
+/// What happened to the mouse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MouseEventKind {
+    Moved,
+    Pressed {
+        button : MouseButton,
+        clicks : u8,
+    },
+    Released {
+        button : MouseButton,
+    },
+    /// The wheel was scrolled, positive `y` is away from the user.
+    Wheel {
+        x : f32,
+        y : f32,
+    },
+    /// The mouse left the window.
+    Left,
+}
+
+/// A mouse event translated into the cells of every layer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseEvent {
+    pub kind :  MouseEventKind,
+    /// The position of the mouse in canvas pixels.
+    pub pixel : (f32, f32),
+    /// The cell under the mouse on each layer, `None` where the mouse is
+    /// outside of the layer.
+    pub cells : Vec<Option<(i32, i32)>>,
+}
+
+impl MouseEvent {
+    /// The cell under the mouse on a layer.
+    #[must_use]
+    pub fn cell(&self, layer : usize) -> Option<(i32, i32)> {
+        self.cells.get(layer).copied().flatten()
+    }
+}
+
+/// The sdl event a mouse event is made from, along with its position in window
+/// coordinates.
+pub(crate) const fn event_kind(event : &Event) -> Option<(MouseEventKind, (f32, f32))> {
+    match *event {
+        Event::MouseMotion { x, y, .. } => Some((MouseEventKind::Moved, (x, y))),
+        Event::MouseButtonDown {
+            mouse_btn,
+            clicks,
+            x,
+            y,
+            ..
+        } => {
+            Some((
+                MouseEventKind::Pressed {
+                    button : mouse_btn,
+                    clicks,
+                },
+                (x, y),
+            ))
+        },
+        Event::MouseButtonUp {
+            mouse_btn, x, y, ..
+        } => Some((MouseEventKind::Released { button : mouse_btn }, (x, y))),
+        Event::MouseWheel {
+            x,
+            y,
+            direction,
+            mouse_x,
+            mouse_y,
+            ..
+        } => {
+            // Natural scrolling reports the wheel turned the other way
+            let (x, y) = if matches!(direction, MouseWheelDirection::Flipped) {
+                (-x, -y)
+            } else {
+                (x, y)
+            };
+
+            Some((MouseEventKind::Wheel { x, y }, (mouse_x, mouse_y)))
+        },
+        Event::Window {
+            win_event: WindowEvent::MouseLeave,
+            ..
+        } => Some((MouseEventKind::Left, (-1.0, -1.0))),
+        _ => None,
+    }
+}
+
+/// Where the mouse is and which buttons are held, kept up to date by
+/// [`Driad::handle_event`](crate::Driad::handle_event).
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Mouse {
+    /// The position of the mouse in canvas pixels, `None` while it is outside
+    /// the window.
+    pub pixel :   Option<(f32, f32)>,
+    /// The cell under the mouse on each layer.
+    pub hovered : Vec<Option<(i32, i32)>>,
+    pub held :    HashSet<MouseButton>,
+}
+
+impl Mouse {
+    pub fn update(&mut self, event : &MouseEvent) {
+        match event.kind {
+            MouseEventKind::Left => {
+                self.pixel = None;
+                self.hovered.clear();
+                return;
+            },
+            MouseEventKind::Pressed { button, .. } => {
+                self.held.insert(button);
+            },
+            MouseEventKind::Released { button } => {
+                self.held.remove(&button);
+            },
+            MouseEventKind::Moved | MouseEventKind::Wheel { .. } => (),
+        }
+
+        self.pixel = Some(event.pixel);
+        self.hovered.clone_from(&event.cells);
+    }
+
+    /// The cell under the mouse on a layer.
+    #[must_use]
+    pub fn hovered(&self, layer : usize) -> Option<(i32, i32)> {
+        self.hovered.get(layer).copied().flatten()
+    }
+
+    /// Publishes the mouse to lua as the global `mouse` table, holding the
+    /// hovered cell of the base layer as `x` and `y`, the hovered cells of
+    /// every layer as `layers` and the held buttons as `held`.
+    ///
+    /// # Errors
+    ///
+    /// If lua fails to allocate the tables.
+    pub fn publish(&self, lua : &Lua) -> Result<(), mlua::Error> {
+        let mouse = lua.create_table()?;
+
+        if let Some((x, y)) = self.hovered(0) {
+            mouse.set("x", x)?;
+            mouse.set("y", y)?;
+        }
+
+        mouse.set("layers", cells_table(lua, &self.hovered)?)?;
+        mouse.set(
+            "held",
+            lua.create_table_from(self.held.iter().map(|button| (button_name(*button), true)))?,
+        )?;
+
+        lua.globals().set("mouse", mouse)
+    }
+}
+
+/// The name a mouse button is given in lua.
+#[must_use]
+pub const fn button_name(button : MouseButton) -> &'static str {
+    match button {
+        MouseButton::Left => "left",
+        MouseButton::Middle => "middle",
+        MouseButton::Right => "right",
+        MouseButton::X1 => "x1",
+        MouseButton::X2 => "x2",
+        MouseButton::Unknown => "unknown",
+    }
+}
+
+/// A table of `{ x, y }` cells indexed by layer, starting from `1` as
+/// everywhere in the lua api. Layers the mouse is outside of are left out.
+fn cells_table(lua : &Lua, cells : &[Option<(i32, i32)>]) -> Result<Table, mlua::Error> {
+    let table = lua.create_table()?;
+
+    for (idx, cell) in cells.iter().enumerate() {
+        if let Some((x, y)) = cell {
+            let pos = lua.create_table()?;
+            pos.set("x", *x)?;
+            pos.set("y", *y)?;
+            table.set(idx + 1, pos)?;
+        }
+    }
+
+    Ok(table)
+}
+
+impl IntoLua for MouseEvent {
+    /// A table with a `kind` of `"move"`, `"press"`, `"release"`, `"wheel"` or
+    /// `"leave"`, the position in pixels as `px` and `py`, the cell on the
+    /// base layer as `x` and `y`, and the cells of every layer as `layers`.
+    /// Presses and releases also have a `button` and presses a `clicks`
+    /// count, while wheel events have `wheel_x` and `wheel_y`.
+    fn into_lua(self, lua : &Lua) -> Result<Value, mlua::Error> {
+        let table = lua.create_table()?;
+
+        let kind = match self.kind {
+            MouseEventKind::Moved => "move",
+            MouseEventKind::Pressed { button, clicks } => {
+                table.set("button", button_name(button))?;
+                table.set("clicks", clicks)?;
+                "press"
+            },
+            MouseEventKind::Released { button } => {
+                table.set("button", button_name(button))?;
+                "release"
+            },
+            MouseEventKind::Wheel { x, y } => {
+                table.set("wheel_x", x)?;
+                table.set("wheel_y", y)?;
+                "wheel"
+            },
+            MouseEventKind::Left => "leave",
+        };
+
+        table.set("kind", kind)?;
+        table.set("px", self.pixel.0)?;
+        table.set("py", self.pixel.1)?;
+
+        if let Some((x, y)) = self.cell(0) {
+            table.set("x", x)?;
+            table.set("y", y)?;
+        }
+
+        table.set("layers", cells_table(lua, &self.cells)?)?;
+
+        Ok(Value::Table(table))
+    }
+}
@@ -1,15 +1,25 @@
 use std::fmt::{self, Display};
 use std::fs::File;
 use std::io::{self, Read};
+use std::mem;
 use std::ops::{Deref, DerefMut};
 use std::path::Path;
 
-use mlua::{Function, Lua, Table};
+use mlua::{Function, Lua, Table, Value};
 use serde::Deserialize;
 use thiserror::Error;
 use toml::de;
 
 use self::version::Version;
+use crate::border::BoxStyle;
+use crate::char::{CP437CharExt, Char437, Shade};
+use crate::color::{Color, Palette};
+use crate::console::CellRect;
+use crate::draw::{DrawCommand, DrawItem, DrawPass};
+use crate::markup;
+use crate::mouse::MouseEvent;
+use crate::text::{HAlign, TextLayout, VAlign};
+use crate::widget::dialog::{Dialog, DialogButton};
 
 pub mod version {
     use std::fmt;
@@ -127,22 +137,28 @@ pub trait PluginApi {
         None
     }
 
-    fn draw_pass(&self) -> Option<Result<DrawCommand, Self::Err>> {
+    fn draw_pass(&self) -> Option<Result<DrawPass, Self::Err>> {
+        None
+    }
+
+    fn mouse_event(&self, _event : &MouseEvent) -> Option<Result<(), Self::Err>> {
         None
     }
 }
 
 pub struct LuaPluginApi {
-    init :      Option<Function>,
-    draw_pass : Option<Function>,
+    init :        Option<Function>,
+    draw_pass :   Option<Function>,
+    mouse_event : Option<Function>,
 }
 
 impl LuaPluginApi {
     #[must_use]
     pub fn new(table : &Table) -> Self {
         Self {
-            init :      table.get("init").ok(),
-            draw_pass : table.get("draw_pass").ok(),
+            init :        table.get("init").ok(),
+            draw_pass :   table.get("draw_pass").ok(),
+            mouse_event : table.get("mouse_event").ok(),
         }
     }
 }
@@ -154,26 +170,312 @@ impl PluginApi for LuaPluginApi {
         self.init.as_ref().map(|init| init.call(()))
     }
 
-    fn draw_pass(&self) -> Option<Result<DrawCommand, Self::Err>> {
+    fn draw_pass(&self) -> Option<Result<DrawPass, Self::Err>> {
         let out = self
             .draw_pass
             .as_ref()
             .map(|draw_pass| draw_pass.call::<Table>(()))?;
 
-        Some(out.and_then(|tab| -> Result<DrawCommand, Self::Err> {
-            Ok(DrawCommand {
-                x :     tab.get("x")?,
-                y :     tab.get("y")?,
-                glyph : tab.get("glyph")?,
-            })
-        }))
+        Some(out.and_then(|tab| draw_pass_from_table(&tab)))
+    }
+
+    fn mouse_event(&self, event : &MouseEvent) -> Option<Result<(), Self::Err>> {
+        self.mouse_event
+            .as_ref()
+            .map(|mouse_event| mouse_event.call(event.clone()))
+    }
+}
+
+/// Reads a draw pass returned from lua. This is either a list of command
+/// tables, or a single command table, an empty table draws nothing.
+///
+/// A command table has a `kind` of `"chr"` (the default), `"str"`,
+/// `"markup"`, `"text"`, `"rect"`, `"box"` or `"icon"`, the fields that kind
+/// needs, and optionally a `layer`, a `z` and `fg`/`bg` colors given as
+/// `{ r, g, b }`. Boxes also take an optional `style` and `title`, and text an
+/// optional `align`, `valign` and `wrap`. Markup is a string of color tags, see
+/// [`markup::parse`].
+///
+/// As everywhere in the lua api, layers are counted from `1`, the base layer,
+/// as is usual in lua, while cell positions start from `0`.
+///
+/// # Errors
+///
+/// If a command is missing a field, has a field of the wrong type, or has a
+/// `layer` of `0`.
+pub fn draw_pass_from_table(tab : &Table) -> Result<DrawPass, mlua::Error> {
+    if tab.is_empty() {
+        Ok(DrawPass::new())
+    } else if tab.raw_len() == 0 {
+        Ok(DrawPass::from(vec![draw_item_from_table(tab)?]))
+    } else {
+        tab.sequence_values::<Table>()
+            .map(|command| draw_item_from_table(&command?))
+            .collect::<Result<Vec<_>, _>>()
+            .map(DrawPass::from)
     }
 }
 
-pub struct DrawCommand {
-    pub x :     i32,
-    pub y :     i32,
-    pub glyph : char,
+fn draw_item_from_table(tab : &Table) -> Result<DrawItem, mlua::Error> {
+    let palette = palette_from_table(tab)?;
+
+    let command = match tab.get::<Option<String>>("kind")?.as_deref() {
+        None | Some("chr") => {
+            DrawCommand::PutChr {
+                pos : pos_from_table(tab)?,
+                chr : char437_from_table(tab, "glyph")?,
+                palette,
+            }
+        },
+        Some("str") => {
+            DrawCommand::PutStr {
+                pos : pos_from_table(tab)?,
+                text : tab.get("text")?,
+                palette,
+            }
+        },
+        Some("markup") => {
+            DrawCommand::PutSpans {
+                pos :   pos_from_table(tab)?,
+                spans : markup::parse(&tab.get::<String>("text")?, palette)
+                    .map_err(mlua::Error::external)?,
+            }
+        },
+        Some("text") => {
+            DrawCommand::PutText {
+                rect : rect_from_table(tab)?,
+                text : tab.get("text")?,
+                layout : text_layout_from_table(tab)?,
+                palette,
+            }
+        },
+        Some("rect") => {
+            DrawCommand::FillRect {
+                rect : rect_from_table(tab)?,
+                chr : char437_from_table(tab, "glyph")?,
+                palette,
+            }
+        },
+        Some("box") => {
+            DrawCommand::DrawBox {
+                rect : rect_from_table(tab)?,
+                style : box_style_from_table(tab)?,
+                title : tab.get("title")?,
+                palette,
+            }
+        },
+        Some("icon") => {
+            DrawCommand::PutIcon {
+                pos : pos_from_table(tab)?,
+                extension : tab.get("extension")?,
+                key : tab.get("key")?,
+                palette,
+            }
+        },
+        Some(kind) => {
+            return Err(mlua::Error::runtime(format!(
+                "Unknown draw command kind: {kind}"
+            )));
+        },
+    };
+
+    let layer = match tab.get::<Option<usize>>("layer")? {
+        None => 0,
+        Some(layer) => {
+            layer
+                .checked_sub(1)
+                .ok_or_else(|| mlua::Error::runtime("Layers are counted from 1"))?
+        },
+    };
+
+    Ok(DrawItem {
+        layer,
+        z : tab.get::<Option<i32>>("z")?.unwrap_or_default(),
+        command,
+    })
+}
+
+/// The dialogs opened from lua since they were last taken.
+#[derive(Default)]
+struct DialogQueue(Vec<Dialog>);
+
+/// Adds the global `open_dialog` function to lua, which takes a table read by
+/// [`dialog_from_table`] and queues the dialog to be opened.
+///
+/// # Errors
+///
+/// If lua fails to create the function.
+pub fn register_dialogs(lua : &Lua) -> Result<(), mlua::Error> {
+    lua.set_app_data(DialogQueue::default());
+
+    let open_dialog = lua.create_function(|lua, tab : Table| {
+        let dialog = dialog_from_table(&tab)?;
+
+        lua.app_data_mut::<DialogQueue>()
+            .ok_or_else(|| mlua::Error::runtime("The dialog queue is missing"))?
+            .0
+            .push(dialog);
+
+        Ok(())
+    })?;
+
+    lua.globals().set("open_dialog", open_dialog)
+}
+
+/// Takes the dialogs opened from lua since this was last called.
+#[must_use]
+pub fn take_dialogs(lua : &Lua) -> Vec<Dialog> {
+    lua.app_data_mut::<DialogQueue>()
+        .map(|mut queue| mem::take(&mut queue.0))
+        .unwrap_or_default()
+}
+
+/// Reads a dialog opened from lua.
+///
+/// The table has a `title`, a `body` and a list of `buttons`, each either a
+/// label or a `{ label, key }` table, which default to a single OK button.
+/// `cancel` is the 1-based index of the button pressed by escape, and
+/// `callback` a function called with the 1-based index and label of the pressed
+/// button.
+///
+/// # Errors
+///
+/// If a field has the wrong type, or `cancel` isn't the index of a button.
+pub fn dialog_from_table(tab : &Table) -> Result<Dialog, mlua::Error> {
+    let mut dialog = Dialog::new(
+        tab.get::<Option<String>>("title")?.unwrap_or_default(),
+        tab.get::<Option<String>>("body")?.unwrap_or_default(),
+    );
+
+    for button in tab
+        .get::<Option<Table>>("buttons")?
+        .iter()
+        .flat_map(|buttons| buttons.sequence_values::<Value>())
+    {
+        dialog = dialog.with_button(match button? {
+            Value::Table(button) => {
+                let key = button.get::<Option<char>>("key")?;
+                let button = DialogButton::new(button.get::<String>("label")?);
+
+                match key {
+                    Some(key) => button.with_shortcut(Some(key)),
+                    None => button,
+                }
+            },
+            label => DialogButton::new(lua_string(label)?),
+        });
+    }
+
+    if dialog.buttons.is_empty() {
+        dialog = dialog.with_button(DialogButton::ok()).with_cancel(0);
+    }
+
+    if let Some(cancel) = tab.get::<Option<usize>>("cancel")? {
+        let count = dialog.buttons.len();
+
+        if !(1 ..= count).contains(&cancel) {
+            return Err(mlua::Error::runtime(format!(
+                "Cancel button {cancel} is not between 1 and {count}"
+            )));
+        }
+
+        dialog = dialog.with_cancel(cancel - 1);
+    }
+
+    if let Some(callback) = tab.get::<Option<Function>>("callback")? {
+        dialog = dialog.with_callback(callback);
+    }
+
+    Ok(dialog)
+}
+
+fn lua_string(value : Value) -> Result<String, mlua::Error> {
+    match value {
+        Value::String(string) => Ok(string.to_str()?.to_owned()),
+        value => {
+            Err(mlua::Error::runtime(format!(
+                "Expected a string, got {}",
+                value.type_name()
+            )))
+        },
+    }
+}
+
+fn pos_from_table(tab : &Table) -> Result<(i32, i32), mlua::Error> {
+    Ok((tab.get("x")?, tab.get("y")?))
+}
+
+fn rect_from_table(tab : &Table) -> Result<CellRect, mlua::Error> {
+    Ok(CellRect::new(
+        tab.get("x")?,
+        tab.get("y")?,
+        tab.get("width")?,
+        tab.get("height")?,
+    ))
+}
+
+fn box_style_from_table(tab : &Table) -> Result<BoxStyle, mlua::Error> {
+    Ok(match tab.get::<Option<String>>("style")?.as_deref() {
+        None | Some("single") => BoxStyle::Single,
+        Some("double") => BoxStyle::Double,
+        Some("double_horizontal") => BoxStyle::DoubleHorizontal,
+        Some("double_vertical") => BoxStyle::DoubleVertical,
+        Some("light") => BoxStyle::Shade(Shade::Light),
+        Some("medium") => BoxStyle::Shade(Shade::Medium),
+        Some("dark") => BoxStyle::Shade(Shade::Dark),
+        Some("full") => BoxStyle::Shade(Shade::Full),
+        Some(style) => {
+            return Err(mlua::Error::runtime(format!("Unknown box style: {style}")));
+        },
+    })
+}
+
+fn text_layout_from_table(tab : &Table) -> Result<TextLayout, mlua::Error> {
+    let h_align = match tab.get::<Option<String>>("align")?.as_deref() {
+        None | Some("left") => HAlign::Left,
+        Some("center") => HAlign::Center,
+        Some("right") => HAlign::Right,
+        Some(align) => {
+            return Err(mlua::Error::runtime(format!("Unknown alignment: {align}")));
+        },
+    };
+
+    let v_align = match tab.get::<Option<String>>("valign")?.as_deref() {
+        None | Some("top") => VAlign::Top,
+        Some("middle") => VAlign::Middle,
+        Some("bottom") => VAlign::Bottom,
+        Some(align) => {
+            return Err(mlua::Error::runtime(format!("Unknown alignment: {align}")));
+        },
+    };
+
+    Ok(TextLayout::new()
+        .aligned(h_align, v_align)
+        .with_wrap(tab.get::<Option<bool>>("wrap")?.unwrap_or(true)))
+}
+
+fn char437_from_table(tab : &Table, key : &str) -> Result<Char437, mlua::Error> {
+    let chr : char = tab.get(key)?;
+    chr.to_cp437()
+        .ok_or_else(|| mlua::Error::runtime(format!("{chr:?} is not a CP437 char")))
+}
+
+fn color_from_table(tab : &Table) -> Result<Color, mlua::Error> {
+    Ok(Color::new(tab.get("r")?, tab.get("g")?, tab.get("b")?))
+}
+
+fn palette_from_table(tab : &Table) -> Result<Palette, mlua::Error> {
+    let mut palette = Palette::default();
+
+    if let Some(fg) = tab.get::<Option<Table>>("fg")? {
+        palette.fg = color_from_table(&fg)?;
+    }
+
+    if let Some(bg) = tab.get::<Option<Table>>("bg")? {
+        palette.bg = color_from_table(&bg)?;
+    }
+
+    Ok(palette)
 }
 
 impl Plugin {
@@ -0,0 +1,239 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::{self, FilterType};
+use image::{Delay, DynamicImage, Frame, ImageError, RgbImage};
+use png::{BitDepth, ColorType, EncodingError};
+use thiserror::Error;
+
+/// Limits on what a [`Recorder`] captures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecorderSettings {
+    /// Only every nth presented frame is considered for capture.
+    pub every_nth :  u32,
+    /// Frames are skipped to keep captures at or below this rate.
+    pub max_fps :    Option<u32>,
+    /// Capturing stops once this many frames are held.
+    pub max_frames : usize,
+    /// Frames larger than this are scaled down to fit, keeping their aspect
+    /// ratio.
+    pub max_size :   Option<(u32, u32)>,
+}
+
+impl Default for RecorderSettings {
+    fn default() -> Self {
+        Self {
+            every_nth :  1,
+            max_fps :    Some(30),
+            // Ten seconds at 30 fps, under 300 MB as every frame is held in
+            // memory
+            max_frames : 300,
+            max_size :   Some((640, 480)),
+        }
+    }
+}
+
+/// Collects presented frames in memory until recording is stopped.
+#[derive(Debug)]
+pub struct Recorder {
+    pub settings : RecorderSettings,
+
+    frames :    Vec<(RgbImage, Instant)>,
+    /// Presented frames seen since the recorder was started.
+    presented : u32,
+}
+
+impl Recorder {
+    #[must_use]
+    pub const fn new(settings : RecorderSettings) -> Self {
+        Self {
+            settings,
+            frames : Vec::new(),
+            presented : 0,
+        }
+    }
+
+    /// Counts a presented frame, returning whether it should be captured.
+    pub fn wants_frame(&mut self) -> bool {
+        let nth = self
+            .presented
+            .is_multiple_of(self.settings.every_nth.max(1));
+        self.presented = self.presented.wrapping_add(1);
+
+        let min_interval = self
+            .settings
+            .max_fps
+            .map_or(Duration::ZERO, |fps| Duration::from_secs(1) / fps.max(1));
+        let rate = self
+            .frames
+            .last()
+            .is_none_or(|(_, at)| at.elapsed() >= min_interval);
+
+        nth && rate && !self.is_full()
+    }
+
+    #[must_use]
+    pub const fn is_full(&self) -> bool {
+        self.frames.len() >= self.settings.max_frames
+    }
+
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Stores a frame, scaling it to fit `max_size`. Frames after the first
+    /// are scaled to the size of the first, so a resized window doesn't break
+    /// the recording.
+    pub fn push(&mut self, frame : RgbImage) {
+        if self.is_full() {
+            return;
+        }
+
+        let (width, height) = match (self.frames.first(), self.settings.max_size) {
+            (Some((first, _)), _) => first.dimensions(),
+            (None, Some((max_width, max_height))) => {
+                fit_within(frame.dimensions(), (max_width, max_height))
+            },
+            (None, None) => frame.dimensions(),
+        };
+
+        let frame = if frame.dimensions() == (width, height) {
+            frame
+        } else {
+            imageops::resize(&frame, width, height, FilterType::Nearest)
+        };
+
+        self.frames.push((frame, Instant::now()));
+    }
+
+    /// Ends the recording, timing each frame by when it was captured.
+    #[must_use]
+    pub fn finish(self) -> Recording {
+        let gaps : Vec<Duration> = self
+            .frames
+            .windows(2)
+            .map(|pair| pair[1].1.duration_since(pair[0].1))
+            .collect();
+        // The last frame has nothing after it, so it lasts as long as the one before.
+        let last = gaps.last().copied().unwrap_or(Duration::from_millis(100));
+
+        Recording {
+            frames : self
+                .frames
+                .into_iter()
+                .map(|(frame, _)| frame)
+                .zip(gaps.into_iter().chain([last]))
+                .collect(),
+        }
+    }
+}
+
+/// The largest size with the aspect ratio of `size` fitting within `max`.
+fn fit_within((width, height) : (u32, u32), (max_width, max_height) : (u32, u32)) -> (u32, u32) {
+    if width <= max_width && height <= max_height {
+        return (width, height);
+    }
+
+    let scale = f64::min(
+        f64::from(max_width) / f64::from(width),
+        f64::from(max_height) / f64::from(height),
+    );
+
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "Scaling down keeps the values within the original u32s"
+    )]
+    let scaled = |len : u32| ((f64::from(len) * scale).floor() as u32).max(1);
+
+    (scaled(width), scaled(height))
+}
+
+/// A finished recording, made of frames and how long each is shown for.
+#[derive(Debug, Default, Clone)]
+pub struct Recording {
+    pub frames : Vec<(RgbImage, Duration)>,
+}
+
+impl Recording {
+    /// Encodes the recording as a looping animated GIF.
+    ///
+    /// # Errors
+    ///
+    /// If the recording is empty, or the file can't be encoded or written.
+    pub fn save_gif(&self, path : impl AsRef<Path>) -> Result<(), RecordingError> {
+        if self.frames.is_empty() {
+            return Err(RecordingError::Empty);
+        }
+
+        let mut encoder = GifEncoder::new_with_speed(BufWriter::new(File::create(path)?), 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(self.frames.iter().map(|(frame, delay)| {
+            Frame::from_parts(
+                DynamicImage::from(frame.clone()).into_rgba8(),
+                0,
+                0,
+                Delay::from_saturating_duration(*delay),
+            )
+        }))?;
+
+        Ok(())
+    }
+
+    /// Encodes the recording as a looping animated PNG.
+    ///
+    /// # Errors
+    ///
+    /// If the recording is empty, or the file can't be encoded or written.
+    pub fn save_apng(&self, path : impl AsRef<Path>) -> Result<(), RecordingError> {
+        let Some((first, _)) = self.frames.first() else {
+            return Err(RecordingError::Empty);
+        };
+
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(File::create(path)?),
+            first.width(),
+            first.height(),
+        );
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(BitDepth::Eight);
+        encoder.set_animated(u32::try_from(self.frames.len()).unwrap_or(u32::MAX), 0)?;
+
+        let mut writer = encoder.write_header()?;
+
+        for (frame, delay) in &self.frames {
+            let millis = u16::try_from(delay.as_millis()).unwrap_or(u16::MAX);
+
+            writer.set_frame_delay(millis, 1000)?;
+            writer.write_image_data(frame.as_raw())?;
+        }
+
+        writer.finish()?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("The recording has no frames")]
+    Empty,
+
+    #[error(transparent)]
+    ImageError(#[from] ImageError),
+
+    #[error(transparent)]
+    PngError(#[from] EncodingError),
+
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+}
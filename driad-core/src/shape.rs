@@ -0,0 +1,240 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::char::Char437;
+use crate::color::Palette;
+use crate::console::{Cell, CellRect, Console};
+
+/// The cells on a line between two points, found with Bresenham's algorithm.
+/// Both ends are included.
+#[must_use]
+pub fn line_points((x0, y0) : (i32, i32), (x1, y1) : (i32, i32)) -> Vec<(i32, i32)> {
+    let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+    let (step_x, step_y) = ((x1 - x0).signum(), (y1 - y0).signum());
+
+    let mut points = Vec::with_capacity(usize::try_from(dx.max(-dy) + 1).unwrap_or_default());
+    let (mut x, mut y) = (x0, y0);
+    let mut err = dx + dy;
+
+    loop {
+        points.push((x, y));
+
+        if x == x1 && y == y1 {
+            return points;
+        }
+
+        let doubled = 2 * err;
+        if doubled >= dy {
+            err += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            err += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// The cells on the outline of an ellipse with radii `rx` and `ry`, found with
+/// the midpoint algorithm. A circle is an ellipse with equal radii.
+#[must_use]
+pub fn ellipse_points((cx, cy) : (i32, i32), (rx, ry) : (i32, i32)) -> Vec<(i32, i32)> {
+    let mut points : Vec<_> = quadrant(rx.abs(), ry.abs())
+        .into_iter()
+        .flat_map(|(x, y)| {
+            [
+                (cx + x, cy + y),
+                (cx - x, cy + y),
+                (cx + x, cy - y),
+                (cx - x, cy - y),
+            ]
+        })
+        .collect();
+
+    points.sort_unstable();
+    points.dedup();
+    points
+}
+
+/// The points of an ellipse centered on the origin with `x` and `y` both
+/// positive.
+fn quadrant(rx : i32, ry : i32) -> Vec<(i32, i32)> {
+    if ry == 0 {
+        return (0 ..= rx).map(|x| (x, 0)).collect();
+    }
+
+    let (rx2, ry2) = (i64::from(rx).pow(2), i64::from(ry).pow(2));
+    let (mut x, mut y) = (0, ry);
+    let (mut dx, mut dy) = (0, 2 * rx2 * i64::from(ry));
+
+    let mut points = Vec::new();
+
+    // Decision values are kept at four times their usual size so they stay
+    // integers.
+    let mut d1 = 4 * ry2 - 4 * rx2 * i64::from(ry) + rx2;
+    while dx < dy {
+        points.push((x, y));
+
+        x += 1;
+        dx += 2 * ry2;
+        if d1 < 0 {
+            d1 += 4 * (dx + ry2);
+        } else {
+            y -= 1;
+            dy -= 2 * rx2;
+            d1 += 4 * (dx - dy + ry2);
+        }
+    }
+
+    let mut d2 =
+        ry2 * (2 * i64::from(x) + 1).pow(2) + 4 * rx2 * (i64::from(y) - 1).pow(2) - 4 * rx2 * ry2;
+    while y >= 0 {
+        points.push((x, y));
+
+        y -= 1;
+        dy -= 2 * rx2;
+        if d2 > 0 {
+            d2 += 4 * (rx2 - dy);
+        } else {
+            x += 1;
+            dx += 2 * ry2;
+            d2 += 4 * (dx - dy + rx2);
+        }
+    }
+
+    points
+}
+
+impl Console {
+    /// Draws a line between two points, both ends included.
+    pub fn draw_line(
+        &mut self,
+        from : (i32, i32),
+        to : (i32, i32),
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        let palette = palette.into();
+
+        for pos in line_points(from, to) {
+            self.set(pos, glyph, palette);
+        }
+    }
+
+    /// Draws the outline of `rect` with a single glyph. See
+    /// [`Self::draw_box`] for borders made of line glyphs.
+    pub fn draw_rect(
+        &mut self,
+        rect : impl Into<CellRect>,
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        let rect = rect.into();
+        let palette = palette.into();
+
+        if rect.is_empty() {
+            return;
+        }
+
+        let (right, bottom) = (rect.right() - 1, rect.bottom() - 1);
+
+        self.draw_line((rect.x, rect.y), (right, rect.y), glyph, palette);
+        self.draw_line((rect.x, bottom), (right, bottom), glyph, palette);
+        self.draw_line((rect.x, rect.y), (rect.x, bottom), glyph, palette);
+        self.draw_line((right, rect.y), (right, bottom), glyph, palette);
+    }
+
+    /// Draws the outline of an ellipse around `center`.
+    pub fn draw_ellipse(
+        &mut self,
+        center : (i32, i32),
+        radii : (i32, i32),
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        let palette = palette.into();
+
+        for pos in ellipse_points(center, radii) {
+            self.set(pos, glyph, palette);
+        }
+    }
+
+    /// Fills an ellipse around `center`, including its outline.
+    pub fn fill_ellipse(
+        &mut self,
+        (cx, cy) : (i32, i32),
+        radii : (i32, i32),
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        let palette = palette.into();
+
+        // The widest point of the outline on each row bounds the span to fill.
+        let mut spans = HashMap::new();
+        for (x, y) in quadrant(radii.0.abs(), radii.1.abs()) {
+            let span = spans.entry(y).or_insert(x);
+            *span = x.max(*span);
+        }
+
+        for (y, half_width) in spans {
+            for row in [cy - y, cy + y] {
+                self.fill(
+                    CellRect::new(cx - half_width, row, 2 * half_width + 1, 1),
+                    glyph,
+                    palette,
+                );
+            }
+        }
+    }
+
+    /// Draws the outline of a circle around `center`.
+    pub fn draw_circle(
+        &mut self,
+        center : (i32, i32),
+        radius : i32,
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        self.draw_ellipse(center, (radius, radius), glyph, palette);
+    }
+
+    /// Fills a circle around `center`, including its outline.
+    pub fn fill_circle(
+        &mut self,
+        center : (i32, i32),
+        radius : i32,
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        self.fill_ellipse(center, (radius, radius), glyph, palette);
+    }
+
+    /// Replaces the cell at `start`, and every cell connected to it through
+    /// its four neighbours that looks exactly the same, with a new glyph and
+    /// palette.
+    pub fn flood_fill(
+        &mut self,
+        start : (i32, i32),
+        glyph : Char437,
+        palette : impl Into<Palette>,
+    ) {
+        let Some(&target) = self.get(start) else {
+            return;
+        };
+
+        let replacement = Cell::new(glyph, palette);
+        if replacement == target {
+            return;
+        }
+
+        let mut queue = VecDeque::from([start]);
+
+        while let Some((x, y)) = queue.pop_front() {
+            match self.get_mut((x, y)) {
+                Some(cell) if *cell == target => *cell = replacement,
+                _ => continue,
+            }
+
+            queue.extend([(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+        }
+    }
+}
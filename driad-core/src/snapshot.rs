@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+use std::{env, io};
+
+use image::{ImageError, Pixel, RgbImage};
+use log::info;
+use sdl3::pixels::PixelFormat;
+use sdl3::render::{Canvas, RenderTarget};
+use thiserror::Error;
+
+/// When this environment variable is set, [`assert_snapshot`] overwrites the
+/// stored images instead of comparing against them.
+pub const UPDATE_SNAPSHOTS_VAR : &str = "DRIAD_UPDATE_SNAPSHOTS";
+
+/// Reads back the current contents of a canvas. Must be called before the
+/// canvas is presented, after which its contents are undefined.
+///
+/// # Errors
+///
+/// If sdl fails to read or convert the pixels of the canvas.
+pub fn read_canvas<T : RenderTarget>(canvas : &Canvas<T>) -> Result<RgbImage, SnapshotError> {
+    let surface = canvas
+        .read_pixels(None)?
+        .convert_format(PixelFormat::RGB24)?;
+
+    let (width, height) = (surface.width(), surface.height());
+    let (pitch, row_len) = (surface.pitch() as usize, 3 * width as usize);
+
+    let bytes = surface.with_lock(|bytes| {
+        bytes
+            .chunks(pitch)
+            .take(height as usize)
+            .flat_map(|row| &row[.. row_len])
+            .copied()
+            .collect()
+    });
+
+    RgbImage::from_raw(width, height, bytes).ok_or(SnapshotError::ShortRead)
+}
+
+/// Counts the pixels of two images that differ by more than `tolerance` in
+/// any channel.
+///
+/// # Errors
+///
+/// If the images aren't the same size.
+pub fn count_differences(
+    actual : &RgbImage,
+    expected : &RgbImage,
+    tolerance : u8,
+) -> Result<usize, SnapshotError> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(SnapshotError::SizeMismatch {
+            actual :   actual.dimensions(),
+            expected : expected.dimensions(),
+        });
+    }
+
+    Ok(actual
+        .pixels()
+        .zip(expected.pixels())
+        .filter(|(a, e)| {
+            a.channels()
+                .iter()
+                .zip(e.channels())
+                .any(|(a, e)| a.abs_diff(*e) > tolerance)
+        })
+        .count())
+}
+
+/// Compares a frame against a stored PNG, allowing each channel to be off by
+/// up to `tolerance`.
+///
+/// While [`UPDATE_SNAPSHOTS_VAR`] is set the frame is written to `path`
+/// instead, so it can be reviewed and committed.
+///
+/// # Errors
+///
+/// If the images differ, if there is no stored image, or if the stored image
+/// couldn't be read or written.
+pub fn assert_snapshot(
+    actual : &RgbImage,
+    path : impl AsRef<Path>,
+    tolerance : u8,
+) -> Result<(), SnapshotError> {
+    let path = path.as_ref();
+
+    if env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        info!("Writing snapshot to {}", path.display());
+        actual.save(path)?;
+        return Ok(());
+    }
+
+    if !path.exists() {
+        return Err(SnapshotError::MissingGolden(path.to_path_buf()));
+    }
+
+    let expected = image::open(path)?.into_rgb8();
+
+    match count_differences(actual, &expected, tolerance)? {
+        0 => Ok(()),
+        differing => {
+            Err(SnapshotError::Mismatch {
+                differing,
+                total : actual.pixels().len(),
+            })
+        },
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    SdlError(#[from] sdl3::Error),
+
+    #[error(transparent)]
+    ImageError(#[from] ImageError),
+
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+
+    #[error("Snapshot is {actual:?} but the stored image is {expected:?}")]
+    SizeMismatch {
+        actual :   (u32, u32),
+        expected : (u32, u32),
+    },
+
+    #[error(
+        "No stored image at {}, set {UPDATE_SNAPSHOTS_VAR} to write it",
+        .0.display()
+    )]
+    MissingGolden(PathBuf),
+
+    #[error("Read back fewer pixels than the canvas holds")]
+    ShortRead,
+
+    #[error("{differing} of {total} pixels differ from the stored image")]
+    Mismatch {
+        differing : usize,
+        total :     usize,
+    },
+}
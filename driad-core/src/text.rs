@@ -0,0 +1,399 @@
+use std::mem;
+
+use sdl3::render::{Canvas, RenderTarget};
+
+use crate::char::{CP437CharExt, Char437};
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::font::{Font, PutGlyphError};
+
+/// Where each line sits between the left and right edges of its rect.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Where the block of lines sits between the top and bottom edges of its rect.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// How text is laid out inside a rect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextLayout {
+    pub h_align :  HAlign,
+    pub v_align :  VAlign,
+    /// Whether lines too long for the rect are broken between words. Lines
+    /// are only ever broken on `\n` otherwise.
+    pub wrap :     bool,
+    /// The glyph put at the end of text cut short by the rect, if any.
+    pub ellipsis : Option<Char437>,
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TextLayout {
+    /// Left and top aligned, wrapping text with `»` marking cut off text.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            h_align :  HAlign::Left,
+            v_align :  VAlign::Top,
+            wrap :     true,
+            ellipsis : Some(Char437(0xAF)),
+        }
+    }
+
+    #[must_use]
+    pub const fn aligned(mut self, h_align : HAlign, v_align : VAlign) -> Self {
+        self.h_align = h_align;
+        self.v_align = v_align;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_wrap(mut self, wrap : bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_ellipsis(mut self, ellipsis : Option<Char437>) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    /// The size `text` takes up when laid out no wider than `max_width`,
+    /// without limiting its height.
+    #[must_use]
+    pub fn measure(&self, text : &str, max_width : i32) -> (i32, i32) {
+        let block = self
+            .with_ellipsis(None)
+            .layout(text, CellRect::new(0, 0, max_width, i32::MAX));
+
+        (block.bounds.width, block.bounds.height)
+    }
+
+    /// Breaks `text` into lines and positions them inside `rect`. Chars that
+    /// don't exist in codepage 437 are laid out as `?`.
+    #[must_use]
+    pub fn layout(&self, text : &str, rect : impl Into<CellRect>) -> TextBlock {
+        let rect = rect.into();
+        let width = usize::try_from(rect.width).unwrap_or_default();
+        let height = usize::try_from(rect.height).unwrap_or_default();
+
+        if width == 0 || height == 0 {
+            return TextBlock {
+                lines :     Vec::new(),
+                bounds :    CellRect::new(rect.x, rect.y, 0, 0),
+                truncated : !text.is_empty(),
+            };
+        }
+
+        let mut lines : Vec<Vec<Char437>> = if self.wrap {
+            text.split('\n')
+                .flat_map(|line| wrap(line, width))
+                .collect()
+        } else {
+            text.split('\n').map(glyphs).collect()
+        };
+
+        let mut truncated = false;
+
+        if lines.len() > height {
+            lines.truncate(height);
+            truncated = true;
+
+            if let (Some(last), Some(ellipsis)) = (lines.last_mut(), self.ellipsis) {
+                if last.len() >= width {
+                    last.truncate(width - 1);
+                }
+                last.push(ellipsis);
+            }
+        }
+
+        for line in &mut lines {
+            if line.len() > width {
+                truncated = true;
+                line.truncate(width);
+
+                if let (Some(last), Some(ellipsis)) = (line.last_mut(), self.ellipsis) {
+                    *last = ellipsis;
+                }
+            }
+        }
+
+        let len = |len : usize| i32::try_from(len).unwrap_or(i32::MAX);
+        let rows = len(lines.len());
+
+        let top = rect.y
+            + match self.v_align {
+                VAlign::Top => 0,
+                VAlign::Middle => (rect.height - rows) / 2,
+                VAlign::Bottom => rect.height - rows,
+            };
+
+        let lines : Vec<TextLine> = (top ..)
+            .zip(lines)
+            .map(|(y, glyphs)| {
+                let x = rect.x
+                    + match self.h_align {
+                        HAlign::Left => 0,
+                        HAlign::Center => (rect.width - len(glyphs.len())) / 2,
+                        HAlign::Right => rect.width - len(glyphs.len()),
+                    };
+
+                TextLine {
+                    pos : (x, y),
+                    glyphs,
+                }
+            })
+            .collect();
+
+        let left = lines.iter().map(|line| line.pos.0).min().unwrap_or(rect.x);
+        let right = lines
+            .iter()
+            .map(|line| line.pos.0 + len(line.glyphs.len()))
+            .max()
+            .unwrap_or(rect.x);
+
+        TextBlock {
+            bounds : CellRect::new(left, top, right - left, rows),
+            lines,
+            truncated,
+        }
+    }
+}
+
+/// A line of laid out text.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TextLine {
+    /// The cell of the first glyph.
+    pub pos :    (i32, i32),
+    pub glyphs : Vec<Char437>,
+}
+
+/// Text laid out by [`TextLayout::layout`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TextBlock {
+    pub lines :     Vec<TextLine>,
+    /// The smallest rect holding every line.
+    pub bounds :    CellRect,
+    /// Whether some of the text didn't fit.
+    pub truncated : bool,
+}
+
+impl TextBlock {
+    /// Every glyph of the block along with its cell.
+    pub fn cells(&self) -> impl Iterator<Item = ((i32, i32), Char437)> {
+        self.lines.iter().flat_map(|line| {
+            let (x, y) = line.pos;
+            (x ..)
+                .zip(line.glyphs.iter().copied())
+                .map(move |(x, glyph)| ((x, y), glyph))
+        })
+    }
+}
+
+fn glyphs(text : &str) -> Vec<Char437> {
+    text.chars()
+        .map(|chr| chr.to_cp437().unwrap_or(Char437(b'?')))
+        .collect()
+}
+
+/// Breaks a line of text between words so no line is wider than `width`.
+/// Words longer than `width` are split over several lines.
+#[must_use]
+pub fn wrap(text : &str, width : usize) -> Vec<Vec<Char437>> {
+    let width = width.max(1);
+
+    let mut lines = Vec::new();
+    let mut line : Vec<Char437> = Vec::new();
+
+    for word in text.split(' ').map(glyphs) {
+        if !line.is_empty() {
+            if line.len() + 1 + word.len() <= width {
+                line.push(Char437(b' '));
+            } else {
+                lines.push(mem::take(&mut line));
+            }
+        }
+
+        let mut rest = word.as_slice();
+        while rest.len() > width {
+            let (head, tail) = rest.split_at(width);
+            lines.push(head.to_vec());
+            rest = tail;
+        }
+
+        line.extend_from_slice(rest);
+    }
+
+    lines.push(line);
+    lines
+}
+
+impl Console {
+    /// Lays out `text` inside `rect` and writes it to the console, returning
+    /// where it ended up.
+    pub fn print_text(
+        &mut self,
+        rect : impl Into<CellRect>,
+        text : &str,
+        layout : &TextLayout,
+        palette : impl Into<Palette>,
+    ) -> TextBlock {
+        let palette = palette.into();
+        let block = layout.layout(text, rect);
+
+        for (pos, glyph) in block.cells() {
+            self.set(pos, glyph, palette);
+        }
+
+        block
+    }
+}
+
+impl Font {
+    /// Lays out `text` inside `rect` and draws it onto the canvas, returning
+    /// where it ended up.
+    ///
+    /// # Errors
+    ///
+    /// See [`Font::put`].
+    pub fn put_text<T : RenderTarget>(
+        &self,
+        canvas : &mut Canvas<T>,
+        rect : impl Into<CellRect>,
+        text : &str,
+        layout : &TextLayout,
+        palette : impl Into<Palette>,
+    ) -> Result<TextBlock, PutGlyphError> {
+        let palette = palette.into();
+        let block = layout.layout(text, rect);
+
+        block
+            .cells()
+            .try_for_each(|(pos, glyph)| self.put_char437(canvas, glyph, pos, palette))?;
+
+        Ok(block)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(glyphs : &[Char437]) -> String {
+        glyphs.iter().copied().map(char::from).collect()
+    }
+
+    fn lines(block : &TextBlock) -> Vec<(i32, i32, String)> {
+        block
+            .lines
+            .iter()
+            .map(|line| (line.pos.0, line.pos.1, text(&line.glyphs)))
+            .collect()
+    }
+
+    #[test]
+    fn wrap_breaks_between_words() {
+        let wrapped : Vec<String> = wrap("a bc def gh", 4)
+            .iter()
+            .map(|line| text(line))
+            .collect();
+
+        assert_eq!(wrapped, ["a bc", "def", "gh"]);
+    }
+
+    #[test]
+    fn wrap_splits_long_words() {
+        let wrapped : Vec<String> = wrap("abcdefgh ij", 3)
+            .iter()
+            .map(|line| text(line))
+            .collect();
+
+        assert_eq!(wrapped, ["abc", "def", "gh", "ij"]);
+    }
+
+    #[test]
+    fn wrap_keeps_empty_lines() {
+        assert_eq!(wrap("", 5), [Vec::new()]);
+    }
+
+    #[test]
+    fn long_lines_end_in_the_ellipsis() {
+        let layout = TextLayout::new().with_wrap(false);
+        let block = layout.layout("abcdefgh", (0, 0, 4, 1));
+
+        assert_eq!(lines(&block), [(0, 0, "abc»".to_owned())]);
+        assert!(block.truncated);
+
+        let block = layout.with_ellipsis(None).layout("abcdefgh", (0, 0, 4, 1));
+
+        assert_eq!(lines(&block), [(0, 0, "abcd".to_owned())]);
+    }
+
+    #[test]
+    fn lines_past_the_bottom_end_in_the_ellipsis() {
+        let block = TextLayout::new().layout("one two three", (0, 0, 5, 2));
+
+        assert_eq!(
+            lines(&block),
+            [(0, 0, "one".to_owned()), (0, 1, "two»".to_owned())]
+        );
+        assert!(block.truncated);
+    }
+
+    #[test]
+    fn lines_are_aligned_inside_the_rect() {
+        let centered = TextLayout::new().aligned(HAlign::Center, VAlign::Middle);
+        let block = centered.layout("ab", (1, 1, 6, 3));
+
+        assert_eq!(lines(&block), [(3, 2, "ab".to_owned())]);
+        assert_eq!(block.bounds, CellRect::new(3, 2, 2, 1));
+
+        let right = TextLayout::new().aligned(HAlign::Right, VAlign::Bottom);
+        let block = right.layout("ab\nc", (0, 0, 6, 3));
+
+        assert_eq!(
+            lines(&block),
+            [(4, 1, "ab".to_owned()), (5, 2, "c".to_owned())]
+        );
+        assert!(!block.truncated);
+    }
+
+    #[test]
+    fn unknown_chars_are_laid_out_as_question_marks() {
+        let block = TextLayout::new().layout("a€b", (0, 0, 5, 1));
+
+        assert_eq!(lines(&block), [(0, 0, "a?b".to_owned())]);
+    }
+
+    #[test]
+    fn measure_ignores_the_height() {
+        assert_eq!(TextLayout::new().measure("hello world", 20), (11, 1));
+        assert_eq!(TextLayout::new().measure("hello world", 5), (5, 2));
+        assert_eq!(TextLayout::new().measure("", 5), (0, 1));
+    }
+
+    #[test]
+    fn empty_rects_truncate_everything() {
+        let block = TextLayout::new().layout("a", (2, 3, 0, 4));
+
+        assert!(block.lines.is_empty());
+        assert!(block.truncated);
+        assert_eq!(block.bounds, CellRect::new(2, 3, 0, 0));
+    }
+}
@@ -1,3 +1,973 @@
-pub struct Widget {
+use std::any::Any;
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
 
+use sdl3::event::Event;
+use sdl3::keyboard::Keycode;
+use sdl3::render::{Canvas, RenderTarget};
+
+use crate::color::{Color, Palette};
+use crate::console::{CellRect, Console};
+use crate::font::{Font, PutGlyphError};
+use crate::input::Modifiers;
+use crate::mouse::{MouseEvent, MouseEventKind};
+use crate::widget::dialog::Dialog;
+
+pub mod dialog;
+pub mod gauge;
+pub mod label;
+pub mod list;
+pub mod message_log;
+pub mod panel;
+pub mod scrollbar;
+pub mod split;
+pub mod table;
+pub mod tabs;
+pub mod text_input;
+pub mod tree;
+
+/// Identifies a [`Node`] for as long as it exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WidgetId(u64);
+
+impl WidgetId {
+    fn next() -> Self {
+        static NEXT : AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// An event as seen by widgets.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UiEvent {
+    Key {
+        key :       Keycode,
+        modifiers : Modifiers,
+        repeat :    bool,
+    },
+    /// Text typed by the user, only sent while sdl's text input is started.
+    Text(String),
+    /// Text pasted from the clipboard, only sent while the focused widget
+    /// [wants text input](Widget::wants_text_input).
+    Paste(String),
+    /// A mouse event, positioned with the cells of the base layer.
+    Mouse(MouseEvent),
+}
+
+impl UiEvent {
+    /// Picks out the events widgets care about. `mouse` is the translated
+    /// form of `event` if it is a mouse event.
+    #[must_use]
+    pub fn from_event(event : &Event, mouse : Option<&MouseEvent>) -> Option<Self> {
+        match event {
+            Event::KeyDown {
+                keycode: Some(key),
+                keymod,
+                repeat,
+                ..
+            } => {
+                Some(Self::Key {
+                    key :       *key,
+                    modifiers : Modifiers::from_mod(*keymod),
+                    repeat :    *repeat,
+                })
+            },
+            Event::TextInput { text, .. } => Some(Self::Text(text.clone())),
+            _ => mouse.cloned().map(Self::Mouse),
+        }
+    }
+
+    /// The cell of a mouse event that has one.
+    #[must_use]
+    pub fn cell(&self) -> Option<(i32, i32)> {
+        match self {
+            Self::Mouse(mouse) if mouse.kind != MouseEventKind::Left => mouse.cell(0),
+            _ => None,
+        }
+    }
+
+    /// The character on the key of a key press without ctrl, alt or gui held,
+    /// upper case if shift is held. Used for hotkeys.
+    #[must_use]
+    pub fn key_char(&self) -> Option<char> {
+        let Self::Key { key, modifiers, .. } = self else {
+            return None;
+        };
+
+        let chr = char::from_u32(key.to_ll().0).filter(char::is_ascii_graphic)?;
+
+        match *modifiers {
+            Modifiers::NONE => Some(chr),
+            Modifiers::SHIFT => Some(chr.to_ascii_uppercase()),
+            _ => None,
+        }
+    }
+
+    /// Whether the event starts something, such as a key or button press,
+    /// rather than ending or moving it.
+    #[must_use]
+    pub const fn is_press(&self) -> bool {
+        match self {
+            Self::Key { .. } | Self::Text(_) | Self::Paste(_) => true,
+            Self::Mouse(mouse) => {
+                matches!(
+                    mouse.kind,
+                    MouseEventKind::Pressed { .. } | MouseEventKind::Wheel { .. }
+                )
+            },
+        }
+    }
+}
+
+/// Whether a widget acted on an event. Consumed events go no further.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventResult {
+    Ignored,
+    Consumed,
+}
+
+impl EventResult {
+    #[must_use]
+    pub const fn is_consumed(self) -> bool {
+        matches!(self, Self::Consumed)
+    }
+}
+
+/// Something a widget wants the host to know about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageKind {
+    /// A message from a widget outside of driad.
+    Custom(String),
+    /// The text of a [`TextInput`](text_input::TextInput) was submitted.
+    Submitted(String),
+    /// An item of a [`List`](list::List) was picked, holding its index.
+    Activated(usize),
+    /// The selected items of a [`List`](list::List) changed, holding their
+    /// indices in order.
+    SelectionChanged(Vec<usize>),
+    /// Enter was pressed in a [`List`](list::List) in
+    /// [`SelectMode::Multi`](list::SelectMode::Multi), holding the indices of
+    /// the selected items in order.
+    Confirmed(Vec<usize>),
+    /// A button of a [`Dialog`](dialog::Dialog) was pressed, holding its
+    /// index. The dialog is removed from the tree once this is sent.
+    DialogClosed(usize),
+    /// A [`Tabs`](tabs::Tabs) switched to another tab, holding its index.
+    TabChanged(usize),
+    /// An item of a [`Tree`](tree::Tree) was activated, holding its path.
+    TreeActivated(Vec<usize>),
+}
+
+/// A message sent by a widget, collected by [`Ui::take_messages`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UiMessage {
+    pub source : WidgetId,
+    pub kind :   MessageKind,
+}
+
+/// What a widget knows while handling an event.
+pub struct EventCtx<'a> {
+    pub id :   WidgetId,
+    /// The area the widget was laid out into.
+    pub rect : CellRect,
+
+    messages : &'a mut Vec<UiMessage>,
+}
+
+impl EventCtx<'_> {
+    /// Sends a message to the host.
+    pub fn emit(&mut self, kind : MessageKind) {
+        self.messages.push(UiMessage {
+            source : self.id,
+            kind,
+        });
+    }
+}
+
+/// What a widget knows while drawing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawCtx {
+    pub id :      WidgetId,
+    /// The area the widget was laid out into.
+    pub rect :    CellRect,
+    /// The palette of the [`Ui`], for widgets without one of their own.
+    pub palette : Palette,
+    /// Whether the widget has keyboard focus.
+    pub focused : bool,
+}
+
+impl DrawCtx {
+    /// `palette` switched to its first accents while the widget is focused,
+    /// so that the focused widget stands out.
+    #[must_use]
+    pub fn highlight(&self, palette : Palette) -> Palette {
+        if self.focused {
+            palette.accent1()
+        } else {
+            palette
+        }
+    }
+}
+
+/// A piece of UI. Widgets are owned by a [`Node`], which holds their children
+/// and tracks when they need to be drawn again.
+pub trait Widget: Any {
+    /// The size the widget would like to be, given the space available.
+    /// Defaults to taking all of it.
+    fn measure(&self, available : (i32, i32), _children : &[Node]) -> (i32, i32) {
+        available
+    }
+
+    /// Places the children inside the area the widget was given. Defaults to
+    /// giving every child the whole area.
+    fn layout(&mut self, rect : CellRect, children : &mut [Node]) {
+        for child in children {
+            child.layout(rect);
+        }
+    }
+
+    /// Draws the widget, before its children are drawn over it.
+    fn draw(&self, _console : &mut Console, _ctx : &DrawCtx) {}
+
+    /// Reacts to an event that none of the widget's children consumed.
+    /// Keyboard events go to the focused widget first and then up through
+    /// its parents.
+    fn handle_event(&mut self, _event : &UiEvent, _ctx : &mut EventCtx) -> EventResult {
+        EventResult::Ignored
+    }
+
+    /// Whether the widget can take keyboard focus.
+    fn focusable(&self) -> bool {
+        false
+    }
+
+    /// Whether focus is kept inside the widget while it is visible, as is done
+    /// for modal dialogs. Events don't reach widgets outside of it.
+    fn traps_focus(&self) -> bool {
+        false
+    }
+
+    /// Called when the widget gains or loses keyboard focus.
+    fn focus_changed(&mut self, _focused : bool) {}
+
+    /// Whether sdl's text input should be started while the widget has focus,
+    /// so that it is sent [`UiEvent::Text`] and [`UiEvent::Paste`].
+    fn wants_text_input(&self) -> bool {
+        false
+    }
+}
+
+/// A widget that draws nothing, placing its children over each other.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stack;
+
+impl Widget for Stack {}
+
+/// A widget in the tree along with its children.
+pub struct Node {
+    id :       WidgetId,
+    widget :   Box<dyn Widget>,
+    children : Vec<Self>,
+
+    rect :    CellRect,
+    visible : bool,
+    /// Whether the node changed since it was last drawn.
+    dirty :   bool,
+}
+
+impl Node {
+    #[must_use]
+    pub fn new(widget : impl Widget) -> Self {
+        Self {
+            id :       WidgetId::next(),
+            widget :   Box::new(widget),
+            children : Vec::new(),
+            rect :     CellRect::default(),
+            visible :  true,
+            dirty :    true,
+        }
+    }
+
+    #[must_use]
+    pub fn with_child(mut self, child : Self) -> Self {
+        self.push(child);
+        self
+    }
+
+    #[must_use]
+    pub const fn id(&self) -> WidgetId {
+        self.id
+    }
+
+    /// The area the node was last laid out into.
+    #[must_use]
+    pub const fn rect(&self) -> CellRect {
+        self.rect
+    }
+
+    #[must_use]
+    pub const fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub const fn set_visible(&mut self, visible : bool) {
+        if self.visible != visible {
+            self.visible = visible;
+            self.dirty = true;
+        }
+    }
+
+    #[must_use]
+    pub fn widget(&self) -> &dyn Widget {
+        self.widget.as_ref()
+    }
+
+    /// The widget, marking the node to be drawn again.
+    pub fn widget_mut(&mut self) -> &mut dyn Widget {
+        self.dirty = true;
+        self.widget.as_mut()
+    }
+
+    /// The widget, if it is a `T`.
+    #[must_use]
+    pub fn downcast_ref<T : Widget>(&self) -> Option<&T> {
+        let widget : &dyn Any = self.widget.as_ref();
+        widget.downcast_ref()
+    }
+
+    /// The widget if it is a `T`, marking the node to be drawn again.
+    pub fn downcast_mut<T : Widget>(&mut self) -> Option<&mut T> {
+        let widget : &mut dyn Any = self.widget.as_mut();
+        let widget = widget.downcast_mut()?;
+        self.dirty = true;
+        Some(widget)
+    }
+
+    #[must_use]
+    pub fn children(&self) -> &[Self] {
+        &self.children
+    }
+
+    /// The children, marking the node to be drawn again.
+    pub const fn children_mut(&mut self) -> &mut Vec<Self> {
+        self.dirty = true;
+        &mut self.children
+    }
+
+    /// Adds a child on top of the others, returning its id.
+    pub fn push(&mut self, child : Self) -> WidgetId {
+        let id = child.id;
+        self.children_mut().push(child);
+        id
+    }
+
+    /// Removes a node from anywhere below this one.
+    pub fn remove(&mut self, id : WidgetId) -> Option<Self> {
+        if let Some(idx) = self.children.iter().position(|child| child.id == id) {
+            self.dirty = true;
+            return Some(self.children.remove(idx));
+        }
+
+        self.children.iter_mut().find_map(|child| child.remove(id))
+    }
+
+    /// Finds this node or one below it.
+    #[must_use]
+    pub fn find(&self, id : WidgetId) -> Option<&Self> {
+        if self.id == id {
+            return Some(self);
+        }
+
+        self.children.iter().find_map(|child| child.find(id))
+    }
+
+    /// Finds this node or one below it.
+    pub fn find_mut(&mut self, id : WidgetId) -> Option<&mut Self> {
+        if self.id == id {
+            return Some(self);
+        }
+
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(id))
+    }
+
+    pub const fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether this node or any below it changed since they were last drawn.
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty || self.children.iter().any(Self::is_dirty)
+    }
+
+    fn clear_dirty(&mut self) {
+        self.dirty = false;
+        for child in &mut self.children {
+            child.clear_dirty();
+        }
+    }
+
+    #[must_use]
+    pub fn measure(&self, available : (i32, i32)) -> (i32, i32) {
+        self.widget.measure(available, &self.children)
+    }
+
+    pub fn layout(&mut self, rect : CellRect) {
+        self.rect = rect;
+        self.widget.layout(rect, &mut self.children);
+    }
+
+    /// Draws the node and then its children, if it is visible.
+    pub fn draw(&self, console : &mut Console, palette : Palette, focused : Option<WidgetId>) {
+        self.draw_dimming(console, palette, focused, None);
+    }
+
+    /// Draws the node and then its children, dimming everything drawn so far
+    /// just before the child at `dim_before` is drawn.
+    fn draw_dimming(
+        &self,
+        console : &mut Console,
+        palette : Palette,
+        focused : Option<WidgetId>,
+        dim_before : Option<usize>,
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        self.widget.draw(
+            console,
+            &DrawCtx {
+                id : self.id,
+                rect : self.rect,
+                palette,
+                focused : focused == Some(self.id),
+            },
+        );
+
+        for (idx, child) in self.children.iter().enumerate() {
+            if dim_before == Some(idx) {
+                dim(console);
+            }
+
+            child.draw(console, palette, focused);
+        }
+    }
+
+    /// The focusable nodes at or below this one in tab order, skipping hidden
+    /// nodes.
+    #[must_use]
+    pub fn focus_chain(&self) -> Vec<(WidgetId, CellRect)> {
+        let mut chain = Vec::new();
+        self.collect_focus_chain(&mut chain);
+        chain
+    }
+
+    fn collect_focus_chain(&self, chain : &mut Vec<(WidgetId, CellRect)>) {
+        if !self.visible {
+            return;
+        }
+
+        if self.widget.focusable() {
+            chain.push((self.id, self.rect));
+        }
+
+        for child in &self.children {
+            child.collect_focus_chain(chain);
+        }
+    }
+
+    /// The topmost visible node at or below this one that traps focus.
+    #[must_use]
+    pub fn focus_trap(&self) -> Option<&Self> {
+        if !self.visible {
+            return None;
+        }
+
+        self.children
+            .iter()
+            .rev()
+            .find_map(Self::focus_trap)
+            .or_else(|| self.widget.traps_focus().then_some(self))
+    }
+
+    /// The topmost visible, focusable node under a cell.
+    #[must_use]
+    pub fn focusable_at(&self, cell : (i32, i32)) -> Option<WidgetId> {
+        if !self.visible || !self.rect.contains(cell) {
+            return None;
+        }
+
+        self.children
+            .iter()
+            .rev()
+            .find_map(|child| child.focusable_at(cell))
+            .or_else(|| self.widget.focusable().then_some(self.id))
+    }
+
+    /// Offers an event to the widget of this node alone.
+    fn handle_own_event(
+        &mut self,
+        event : &UiEvent,
+        messages : &mut Vec<UiMessage>,
+    ) -> EventResult {
+        let result = self.widget.handle_event(
+            event,
+            &mut EventCtx {
+                id : self.id,
+                rect : self.rect,
+                messages,
+            },
+        );
+
+        if result.is_consumed() {
+            self.dirty = true;
+        }
+
+        result
+    }
+
+    /// Offers an event to the node `id` and then to each of its parents up to
+    /// this one, until one consumes it. `None` if `id` isn't below this node.
+    fn bubble_event(
+        &mut self,
+        id : WidgetId,
+        event : &UiEvent,
+        messages : &mut Vec<UiMessage>,
+    ) -> Option<EventResult> {
+        if self.id == id {
+            return Some(self.handle_own_event(event, messages));
+        }
+
+        let result = self
+            .children
+            .iter_mut()
+            .find_map(|child| child.bubble_event(id, event, messages))?;
+
+        if result.is_consumed() {
+            return Some(result);
+        }
+
+        Some(self.handle_own_event(event, messages))
+    }
+
+    /// Offers an event to the children from the top down, then to this node's
+    /// widget if none of them consumed it. Mouse events only go to nodes under
+    /// the mouse.
+    pub fn handle_event(
+        &mut self,
+        event : &UiEvent,
+        messages : &mut Vec<UiMessage>,
+    ) -> EventResult {
+        if !self.visible {
+            return EventResult::Ignored;
+        }
+
+        if matches!(event, UiEvent::Mouse(_))
+            && !event.cell().is_some_and(|cell| self.rect.contains(cell))
+        {
+            return EventResult::Ignored;
+        }
+
+        for child in self.children.iter_mut().rev() {
+            if child.handle_event(event, messages).is_consumed() {
+                return EventResult::Consumed;
+            }
+        }
+
+        self.handle_own_event(event, messages)
+    }
+}
+
+/// Halves the brightness of every cell, as is done beneath a dialog.
+fn dim(console : &mut Console) {
+    for pos in console.rect().positions() {
+        if let Some(cell) = console.get_mut(pos) {
+            cell.fg = cell.fg.dimmed();
+            cell.bg = cell.bg.map(Color::dimmed);
+        }
+    }
+}
+
+/// The center of a rect, doubled to stay in whole cells.
+const fn center(rect : CellRect) -> (i32, i32) {
+    (2 * rect.x + rect.width, 2 * rect.y + rect.height)
+}
+
+/// The root of the widget tree, drawn into its own console over every layer.
+/// It is only laid out and drawn again when something in it changed.
+///
+/// One focusable widget at a time may have keyboard focus. Tab and Shift+Tab
+/// move focus along the [focus chain](Node::focus_chain) and clicking a
+/// focusable widget focuses it.
+pub struct Ui {
+    pub root :             Node,
+    /// The palette widgets draw with unless they have their own. Focused
+    /// widgets are drawn with its first accents.
+    pub palette :          Palette,
+    /// Whether the arrow keys the focused widget ignores move focus to the
+    /// nearest focusable widget in that direction. Off by default so that the
+    /// game keeps its arrow bindings.
+    pub arrow_navigation : bool,
+
+    console :  Console,
+    messages : Vec<UiMessage>,
+    focused :  Option<WidgetId>,
+}
+
+impl Ui {
+    /// Creates an empty UI covering `width` by `height` cells.
+    #[must_use]
+    pub fn new(width : i32, height : i32) -> Self {
+        Self {
+            root :             Node::new(Stack),
+            palette :          Palette::default()
+                .with_accent1(Color::new(0, 0, 0), Color::new(255, 255, 255)),
+            arrow_navigation : false,
+            console :          Console::new(width, height),
+            messages :         Vec::new(),
+            focused :          None,
+        }
+    }
+
+    /// Resizes the UI, laying it out again if the size changed.
+    pub fn resize(&mut self, width : i32, height : i32) {
+        if (width, height) != (self.console.width(), self.console.height()) {
+            self.console = Console::new(width, height);
+            self.root.mark_dirty();
+        }
+    }
+
+    /// Adds a node on top of the others, returning its id.
+    pub fn add(&mut self, node : Node) -> WidgetId {
+        self.root.push(node)
+    }
+
+    pub fn remove(&mut self, id : WidgetId) -> Option<Node> {
+        self.root.remove(id)
+    }
+
+    #[must_use]
+    pub fn find(&self, id : WidgetId) -> Option<&Node> {
+        self.root.find(id)
+    }
+
+    pub fn find_mut(&mut self, id : WidgetId) -> Option<&mut Node> {
+        self.root.find_mut(id)
+    }
+
+    /// The widget of a node, if it is a `T`.
+    #[must_use]
+    pub fn widget<T : Widget>(&self, id : WidgetId) -> Option<&T> {
+        self.find(id)?.downcast_ref()
+    }
+
+    /// The widget of a node if it is a `T`, marking it to be drawn again.
+    pub fn widget_mut<T : Widget>(&mut self, id : WidgetId) -> Option<&mut T> {
+        self.find_mut(id)?.downcast_mut()
+    }
+
+    /// Passes an event through the tree, returning whether a widget consumed
+    /// it.
+    ///
+    /// Keyboard events go to the focused widget and then up through its
+    /// parents, and are used to move focus if none of them consumed it. With
+    /// nothing focused they only reach a focus trap, and are otherwise left to
+    /// the game. While a widget
+    /// [traps focus](Widget::traps_focus) events only reach the widgets inside
+    /// it, and key and mouse presses are consumed even if no widget acted on
+    /// them.
+    pub fn handle_event(&mut self, event : &UiEvent) -> bool {
+        self.fix_focus();
+
+        let trap = self.root.focus_trap().map(Node::id);
+        let scope_id = trap.unwrap_or_else(|| self.root.id());
+
+        if let UiEvent::Mouse(mouse) = event
+            && let MouseEventKind::Pressed { .. } = mouse.kind
+            && let Some(cell) = event.cell()
+        {
+            match self.scope().focusable_at(cell) {
+                Some(id) => self.focus_id(Some(id)),
+                None if trap.is_none() => self.blur(),
+                None => (),
+            }
+        }
+
+        let Some(scope) = self.root.find_mut(scope_id) else {
+            return false;
+        };
+
+        let keyboard = matches!(
+            event,
+            UiEvent::Key { .. } | UiEvent::Text(_) | UiEvent::Paste(_)
+        );
+        let sent = self.messages.len();
+
+        let consumed = match (self.focused, keyboard) {
+            (Some(focused), true) => {
+                scope
+                    .bubble_event(focused, event, &mut self.messages)
+                    .is_some_and(EventResult::is_consumed)
+            },
+            (None, true) if trap.is_none() => false,
+            _ => scope.handle_event(event, &mut self.messages).is_consumed(),
+        };
+
+        let navigated = !consumed
+            && match *event {
+                UiEvent::Key { key, modifiers, .. } => self.navigate(key, modifiers),
+                _ => false,
+            };
+
+        // Nothing beneath a focus trap should react to presses
+        let blocked = trap.is_some() && event.is_press();
+
+        self.close_dialogs(sent);
+
+        consumed || navigated || blocked
+    }
+
+    /// Opens a dialog over everything else, returning its id. The dialog sends
+    /// a [`MessageKind::DialogClosed`] when it closes.
+    pub fn open_dialog(&mut self, dialog : Dialog) -> WidgetId {
+        self.add(Node::new(dialog))
+    }
+
+    /// Removes the dialogs that sent a [`MessageKind::DialogClosed`] after the
+    /// first `sent` messages.
+    fn close_dialogs(&mut self, sent : usize) {
+        let closed : Vec<WidgetId> = self.messages[sent ..]
+            .iter()
+            .filter(|message| matches!(message.kind, MessageKind::DialogClosed(_)))
+            .map(|message| message.source)
+            .collect();
+
+        for id in closed {
+            self.remove(id);
+        }
+    }
+
+    /// Whether a visible dialog wants everything beneath it dimmed.
+    #[must_use]
+    pub fn dims_backdrop(&self) -> bool {
+        self.backdrop().is_some()
+    }
+
+    /// The index of the topmost visible dialog that dims everything beneath
+    /// it, among the children of the root.
+    fn backdrop(&self) -> Option<usize> {
+        self.root.children().iter().rposition(|node| {
+            node.is_visible()
+                && node
+                    .downcast_ref::<Dialog>()
+                    .is_some_and(|dialog| dialog.dim_backdrop)
+        })
+    }
+
+    /// The node focus is kept inside of, the trapping node if there is one.
+    fn scope(&self) -> &Node {
+        self.root.focus_trap().unwrap_or(&self.root)
+    }
+
+    /// The focusable widgets that can currently be focused, in tab order.
+    #[must_use]
+    pub fn focus_chain(&self) -> Vec<WidgetId> {
+        self.scope()
+            .focus_chain()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// The widget with keyboard focus.
+    #[must_use]
+    pub const fn focused(&self) -> Option<WidgetId> {
+        self.focused
+    }
+
+    /// Whether the focused widget wants typed text.
+    #[must_use]
+    pub fn wants_text_input(&self) -> bool {
+        self.focused
+            .and_then(|id| self.find(id))
+            .is_some_and(|node| node.widget.wants_text_input())
+    }
+
+    /// Gives keyboard focus to a widget, returning whether it could take it.
+    /// Hidden widgets, widgets that aren't focusable and widgets outside of a
+    /// focus trap can't be focused.
+    pub fn focus(&mut self, id : WidgetId) -> bool {
+        if !self.focus_chain().contains(&id) {
+            return false;
+        }
+
+        self.focus_id(Some(id));
+        true
+    }
+
+    /// Takes keyboard focus away from whichever widget has it.
+    pub fn blur(&mut self) {
+        self.focus_id(None);
+    }
+
+    /// Moves focus to the next widget in the focus chain, wrapping around.
+    pub fn focus_next(&mut self) {
+        self.cycle_focus(true);
+    }
+
+    /// Moves focus to the previous widget in the focus chain, wrapping around.
+    pub fn focus_prev(&mut self) {
+        self.cycle_focus(false);
+    }
+
+    fn cycle_focus(&mut self, forward : bool) {
+        let chain = self.focus_chain();
+        if chain.is_empty() {
+            return;
+        }
+
+        let current = self
+            .focused
+            .and_then(|focused| chain.iter().position(|id| *id == focused));
+
+        let next = match (current, forward) {
+            (Some(idx), true) => (idx + 1) % chain.len(),
+            (Some(idx), false) => (idx + chain.len() - 1) % chain.len(),
+            (None, true) => 0,
+            (None, false) => chain.len() - 1,
+        };
+
+        self.focus_id(Some(chain[next]));
+    }
+
+    /// Moves focus to the nearest focusable widget in the direction of
+    /// `(dx, dy)`, returning whether there was one.
+    pub fn focus_towards(&mut self, (dx, dy) : (i32, i32)) -> bool {
+        let chain = self.scope().focus_chain();
+
+        let Some(from) = self
+            .focused
+            .and_then(|focused| chain.iter().find(|(id, _)| *id == focused))
+            .map(|(_, rect)| center(*rect))
+        else {
+            return false;
+        };
+
+        let nearest = chain
+            .iter()
+            .filter(|(id, _)| Some(*id) != self.focused)
+            .filter_map(|(id, rect)| {
+                let to = center(*rect);
+                let along = (to.0 - from.0) * dx + (to.1 - from.1) * dy;
+                let across = ((to.0 - from.0) * dy).abs() + ((to.1 - from.1) * dx).abs();
+
+                (along > 0).then_some((along + 2 * across, *id))
+            })
+            .min_by_key(|(distance, _)| *distance);
+
+        if let Some((_, id)) = nearest {
+            self.focus_id(Some(id));
+        }
+
+        nearest.is_some()
+    }
+
+    /// Handles the keys that move focus, returning whether focus moved.
+    fn navigate(&mut self, key : Keycode, modifiers : Modifiers) -> bool {
+        let towards = match (key, modifiers) {
+            (Keycode::Tab, Modifiers::NONE) => {
+                self.focus_next();
+                return true;
+            },
+            (Keycode::Tab, Modifiers::SHIFT) => {
+                self.focus_prev();
+                return true;
+            },
+            _ if !self.arrow_navigation => return false,
+            (Keycode::Up, Modifiers::NONE) => (0, -1),
+            (Keycode::Down, Modifiers::NONE) => (0, 1),
+            (Keycode::Left, Modifiers::NONE) => (-1, 0),
+            (Keycode::Right, Modifiers::NONE) => (1, 0),
+            _ => return false,
+        };
+
+        self.focus_towards(towards)
+    }
+
+    /// Moves focus, letting the widgets that gained and lost it know.
+    fn focus_id(&mut self, id : Option<WidgetId>) {
+        if id == self.focused {
+            return;
+        }
+
+        for (id, focused) in [(self.focused, false), (id, true)] {
+            if let Some(node) = id.and_then(|id| self.root.find_mut(id)) {
+                node.mark_dirty();
+                node.widget.focus_changed(focused);
+            }
+        }
+
+        self.focused = id;
+    }
+
+    /// Drops focus from a widget that was removed, hidden or left outside of a
+    /// focus trap, moving it into the trap if there is one.
+    fn fix_focus(&mut self) {
+        let chain = self.focus_chain();
+
+        if self.focused.is_some_and(|focused| chain.contains(&focused)) {
+            return;
+        }
+
+        let trapped = self.root.focus_trap().is_some();
+        self.focus_id(chain.first().copied().filter(|_| trapped));
+    }
+
+    /// The messages sent by widgets since this was last called.
+    pub fn take_messages(&mut self) -> Vec<UiMessage> {
+        mem::take(&mut self.messages)
+    }
+
+    /// Lays out and draws the tree into the console if anything changed.
+    pub fn refresh(&mut self) {
+        self.fix_focus();
+
+        if !self.root.is_dirty() {
+            return;
+        }
+
+        // Stacked dialogs dim what is beneath the topmost one only once
+        let backdrop = self.backdrop();
+
+        self.root.layout(self.console.rect());
+        self.console.clear();
+        self.root
+            .draw_dimming(&mut self.console, self.palette, self.focused, backdrop);
+        self.root.clear_dirty();
+    }
+
+    /// The console the UI is drawn into, as of the last [`Self::refresh`].
+    #[must_use]
+    pub const fn console(&self) -> &Console {
+        &self.console
+    }
+
+    /// Refreshes the UI and draws it onto the canvas with `font`, its top
+    /// left cell at the pixel `offset`.
+    ///
+    /// # Errors
+    ///
+    /// See [`Font::flush`].
+    pub fn draw<T : RenderTarget>(
+        &mut self,
+        canvas : &mut Canvas<T>,
+        font : &Font,
+        offset : (i32, i32),
+    ) -> Result<(), PutGlyphError> {
+        self.refresh();
+        font.flush(canvas, &self.console, offset)
+    }
 }
@@ -0,0 +1,446 @@
+use log::warn;
+use mlua::Function;
+use sdl3::keyboard::Keycode;
+use sdl3::mouse::MouseButton;
+
+use crate::border::BoxStyle;
+use crate::char::Char437;
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::input::Modifiers;
+use crate::mouse::MouseEventKind;
+use crate::text::TextLayout;
+use crate::widget::{DrawCtx, EventCtx, EventResult, MessageKind, Node, UiEvent, Widget};
+
+/// Empty cells between the border of a dialog and its contents.
+const PADDING : i32 = 1;
+
+/// Empty cells between neighbouring buttons.
+const BUTTON_GAP : i32 = 2;
+
+/// A button along the bottom of a [`Dialog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialogButton {
+    pub label :    String,
+    /// Presses the button when typed, defaulting to the first letter of the
+    /// label in lower case.
+    pub shortcut : Option<char>,
+}
+
+impl DialogButton {
+    #[must_use]
+    pub fn new(label : impl Into<String>) -> Self {
+        let label = label.into();
+
+        Self {
+            shortcut : label.chars().next().map(|chr| chr.to_ascii_lowercase()),
+            label,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_shortcut(mut self, shortcut : Option<char>) -> Self {
+        self.shortcut = shortcut;
+        self
+    }
+
+    #[must_use]
+    pub fn ok() -> Self {
+        Self::new("OK")
+    }
+
+    #[must_use]
+    pub fn cancel() -> Self {
+        Self::new("Cancel")
+    }
+
+    #[must_use]
+    pub fn yes() -> Self {
+        Self::new("Yes")
+    }
+
+    #[must_use]
+    pub fn no() -> Self {
+        Self::new("No")
+    }
+
+    /// The width of the button as drawn, `< label >`.
+    fn width(&self) -> i32 {
+        i32::try_from(self.label.chars().count())
+            .unwrap_or(i32::MAX)
+            .saturating_add(4)
+    }
+}
+
+/// A framed panel centered over the screen that keeps focus until one of its
+/// buttons is pressed, such as a message box or a confirmation prompt.
+///
+/// Dialogs are opened with [`Ui::open_dialog`](super::Ui::open_dialog) or by
+/// adding them to the tree, with any children laid out between the body and
+/// the buttons. The arrow keys pick a button and enter presses it, as does
+/// clicking it or typing its shortcut, while escape presses the cancel button.
+/// Pressing a button sends a [`MessageKind::DialogClosed`] holding its index,
+/// calls the lua callback if there is one, and closes the dialog. A dialog
+/// without buttons is given an OK button, pressed by escape. Dialogs opened
+/// over each other stack, the topmost one keeping focus.
+#[derive(Debug, Clone)]
+pub struct Dialog {
+    pub title :        Option<String>,
+    /// Wrapped to fit inside the dialog.
+    pub body :         String,
+    pub buttons :      Vec<DialogButton>,
+    /// The button pressed by enter.
+    pub selected :     usize,
+    /// The button pressed by escape, if any.
+    pub cancel :       Option<usize>,
+    pub style :        BoxStyle,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette :      Option<Palette>,
+    /// Whether everything beneath the dialog is drawn dimmed, once however
+    /// many dialogs are stacked. Only applies to dialogs at the top of the
+    /// tree, as opened by [`Ui::open_dialog`](super::Ui::open_dialog).
+    pub dim_backdrop : bool,
+    /// The widest the dialog grows, border included.
+    pub max_width :    i32,
+
+    /// Called with the 1-based index and label of the pressed button.
+    callback : Option<Function>,
+
+    /// The area inside the frame, as of the last layout.
+    frame :        CellRect,
+    body_rect :    CellRect,
+    button_rects : Vec<CellRect>,
+}
+
+impl Default for Dialog {
+    fn default() -> Self {
+        Self {
+            title :        None,
+            body :         String::new(),
+            buttons :      Vec::new(),
+            selected :     0,
+            cancel :       None,
+            style :        BoxStyle::Double,
+            palette :      None,
+            dim_backdrop : true,
+            max_width :    50,
+            callback :     None,
+            frame :        CellRect::default(),
+            body_rect :    CellRect::default(),
+            button_rects : Vec::new(),
+        }
+    }
+}
+
+impl Dialog {
+    #[must_use]
+    pub fn new(title : impl Into<String>, body : impl Into<String>) -> Self {
+        Self {
+            title : Some(title.into()),
+            body : body.into(),
+            ..Self::default()
+        }
+    }
+
+    /// A dialog with a single OK button.
+    #[must_use]
+    pub fn message(title : impl Into<String>, body : impl Into<String>) -> Self {
+        Self::new(title, body)
+            .with_button(DialogButton::ok())
+            .with_cancel(0)
+    }
+
+    /// A dialog asking yes or no, escape answering no.
+    #[must_use]
+    pub fn confirm(title : impl Into<String>, body : impl Into<String>) -> Self {
+        Self::new(title, body)
+            .with_button(DialogButton::yes())
+            .with_button(DialogButton::no())
+            .with_cancel(1)
+    }
+
+    #[must_use]
+    pub fn with_button(mut self, button : DialogButton) -> Self {
+        self.buttons.push(button);
+        self
+    }
+
+    /// Sets the button pressed by escape.
+    #[must_use]
+    pub const fn with_cancel(mut self, cancel : usize) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Sets the button pressed by enter when the dialog opens.
+    #[must_use]
+    pub const fn with_selected(mut self, selected : usize) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_style(mut self, style : BoxStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_dim_backdrop(mut self, dim_backdrop : bool) -> Self {
+        self.dim_backdrop = dim_backdrop;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_width(mut self, max_width : i32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Calls a lua function with the 1-based index and the label of the
+    /// pressed button when the dialog closes.
+    #[must_use]
+    pub fn with_callback(mut self, callback : Function) -> Self {
+        self.callback = Some(callback);
+        self
+    }
+
+    /// The area inside the border, as of the last layout.
+    #[must_use]
+    pub const fn frame(&self) -> CellRect {
+        self.frame
+    }
+
+    fn buttons_width(&self) -> i32 {
+        let gaps = i32::try_from(self.buttons.len().saturating_sub(1)).unwrap_or(0);
+
+        self.buttons
+            .iter()
+            .map(DialogButton::width)
+            .sum::<i32>()
+            .saturating_add(gaps * BUTTON_GAP)
+    }
+
+    /// Presses a button, closing the dialog.
+    /// Gives a dialog without buttons an OK button pressed by escape, so that
+    /// it can always be closed.
+    fn ensure_button(&mut self) {
+        if self.buttons.is_empty() {
+            self.buttons.push(DialogButton::ok());
+            self.cancel = Some(0);
+        }
+    }
+
+    fn press(&mut self, idx : usize, ctx : &mut EventCtx) -> bool {
+        let Some(button) = self.buttons.get(idx) else {
+            return false;
+        };
+
+        self.selected = idx;
+
+        if let Some(callback) = &self.callback
+            && let Err(err) = callback.call::<()>((idx + 1, button.label.clone()))
+        {
+            warn!("Dialog callback failed: {err}");
+        }
+
+        ctx.emit(MessageKind::DialogClosed(idx));
+        true
+    }
+
+    fn handle_key(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> bool {
+        let UiEvent::Key { key, modifiers, .. } = *event else {
+            return false;
+        };
+
+        if let Some(chr) = event.key_char()
+            && let Some(idx) = self
+                .buttons
+                .iter()
+                .position(|button| button.shortcut == Some(chr))
+        {
+            return self.press(idx, ctx);
+        }
+
+        if modifiers != Modifiers::NONE {
+            return false;
+        }
+
+        match key {
+            Keycode::Left => self.selected = self.selected.saturating_sub(1),
+            Keycode::Right => {
+                self.selected = (self.selected + 1).min(self.buttons.len().saturating_sub(1));
+            },
+            Keycode::Return | Keycode::KpEnter => return self.press(self.selected, ctx),
+            Keycode::Escape => return self.cancel.is_some_and(|cancel| self.press(cancel, ctx)),
+            _ => return false,
+        }
+
+        true
+    }
+}
+
+impl Widget for Dialog {
+    fn layout(&mut self, rect : CellRect, children : &mut [Node]) {
+        self.ensure_button();
+
+        let inset = 2 * (1 + PADDING);
+        let max_inner = self.max_width.min(rect.width - 2) - inset;
+
+        let body_layout = TextLayout::new();
+        let (body_width, body_height) = if self.body.is_empty() {
+            (0, 0)
+        } else {
+            body_layout.measure(&self.body, max_inner)
+        };
+
+        let title = self.title.as_ref().map_or(0, |title| {
+            i32::try_from(title.chars().count())
+                .unwrap_or(i32::MAX)
+                .saturating_add(2)
+        });
+
+        // The row of buttons and the gap above it
+        let buttons = 2;
+        let spare_height = rect.height - inset - body_height - buttons;
+
+        let (children_width, children_height) = children
+            .iter()
+            .map(|child| child.measure((max_inner, spare_height)))
+            .fold((0, 0), |(w, h), (child_w, child_h)| {
+                (w.max(child_w), h.max(child_h))
+            });
+
+        let gap = i32::from(body_height > 0 && children_height > 0);
+
+        let inner_width = body_width
+            .max(title)
+            .max(self.buttons_width())
+            .max(children_width)
+            .clamp(0, max_inner.max(0));
+        let inner_height = body_height + gap + children_height + buttons;
+
+        let width = (inner_width + inset).min(rect.width);
+        let height = (inner_height + inset).min(rect.height);
+
+        self.frame = CellRect::new(
+            rect.x + (rect.width - width) / 2,
+            rect.y + (rect.height - height) / 2,
+            width,
+            height,
+        );
+
+        let inner = CellRect::new(
+            self.frame.x + 1 + PADDING,
+            self.frame.y + 1 + PADDING,
+            inner_width,
+            (height - inset).max(0),
+        );
+
+        self.body_rect = CellRect::new(inner.x, inner.y, inner.width, body_height);
+
+        let content = CellRect::new(
+            inner.x,
+            inner.y + body_height + gap,
+            inner.width,
+            (inner.height - body_height - gap - buttons).max(0),
+        );
+
+        for child in children {
+            child.layout(content);
+        }
+
+        let mut x = inner.x + (inner.width - self.buttons_width()).max(0) / 2;
+        let y = inner.bottom() - 1;
+
+        self.button_rects = self
+            .buttons
+            .iter()
+            .map(|button| {
+                let rect = CellRect::new(x, y, button.width(), 1);
+                x += button.width() + BUTTON_GAP;
+                rect
+            })
+            .collect();
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = self.palette.unwrap_or(ctx.palette);
+
+        console.fill(self.frame, Char437(b' '), palette);
+
+        match &self.title {
+            Some(title) => console.draw_titled_box(self.frame, self.style, palette, title),
+            None => console.draw_box(self.frame, self.style, palette),
+        }
+
+        console.print_text(self.body_rect, &self.body, &TextLayout::new(), palette);
+
+        for (idx, (button, rect)) in self.buttons.iter().zip(&self.button_rects).enumerate() {
+            let button_palette = if idx == self.selected {
+                palette.accent1()
+            } else {
+                palette
+            };
+
+            console.print(
+                (rect.x, rect.y),
+                &format!("< {} >", button.label),
+                button_palette,
+            );
+
+            // Mark the shortcut where it appears in the label
+            if let Some(shortcut) = button.shortcut
+                && let Some(offset) = button
+                    .label
+                    .chars()
+                    .position(|chr| chr.eq_ignore_ascii_case(&shortcut))
+                && let Some(cell) =
+                    console.get_mut((rect.x + 2 + i32::try_from(offset).unwrap_or(0), rect.y))
+            {
+                cell.fg = button_palette.fg_accent2();
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> EventResult {
+        self.ensure_button();
+
+        let consumed = match event {
+            UiEvent::Key { .. } => self.handle_key(event, ctx),
+            UiEvent::Mouse(mouse) => {
+                match mouse.kind {
+                    MouseEventKind::Pressed {
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        event.cell().is_some_and(|cell| {
+                            self.button_rects
+                                .iter()
+                                .position(|rect| rect.contains(cell))
+                                .is_some_and(|idx| self.press(idx, ctx))
+                        })
+                    },
+                    _ => false,
+                }
+            },
+            UiEvent::Text(_) | UiEvent::Paste(_) => false,
+        };
+
+        if consumed {
+            EventResult::Consumed
+        } else {
+            EventResult::Ignored
+        }
+    }
+
+    fn traps_focus(&self) -> bool {
+        true
+    }
+}
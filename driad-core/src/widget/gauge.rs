@@ -0,0 +1,231 @@
+use crate::char::{CP437CharExt, Char437, Shade};
+use crate::color::Palette;
+use crate::console::Console;
+use crate::layout::Direction;
+use crate::widget::{DrawCtx, Node, Widget};
+
+/// The glyph drawn for a cell of the bar `covered` full, from `0.0` to `1.0`,
+/// in quarters. `half` is the half block facing the start of the bar.
+fn fill_glyph(covered : f64, half : Char437) -> Char437 {
+    if covered < 0.125 {
+        Char437(b' ')
+    } else if covered < 0.375 {
+        Shade::Light.glyph()
+    } else if covered < 0.625 {
+        half
+    } else if covered < 0.875 {
+        Shade::Dark.glyph()
+    } else {
+        Shade::Full.glyph()
+    }
+}
+
+/// A bar filled in proportion to a value, such as a health or experience bar.
+///
+/// The end of the fill is drawn in quarter cells with the shade and half block
+/// glyphs (`░▌▓█`). The filled part is drawn with the foreground of the
+/// palette and the rest with its background. A label is drawn over the middle
+/// of the bar, inverted where it covers the fill.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gauge {
+    pub value :      f64,
+    pub max :        f64,
+    /// Filled left to right when horizontal, bottom to top when vertical.
+    pub direction :  Direction,
+    /// Fills from the other end, right to left or top to bottom.
+    pub reversed :   bool,
+    pub label :      String,
+    /// Whether `value/max` is added to the label.
+    pub show_value : bool,
+    /// Palettes used once the bar is at or below a fraction full, such as red
+    /// for low health.
+    pub thresholds : Vec<(f64, Palette)>,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette :    Option<Palette>,
+}
+
+impl Gauge {
+    #[must_use]
+    pub const fn new(value : f64, max : f64) -> Self {
+        Self {
+            value,
+            max,
+            direction : Direction::Horizontal,
+            reversed : false,
+            label : String::new(),
+            show_value : false,
+            thresholds : Vec::new(),
+            palette : None,
+        }
+    }
+
+    /// A gauge filling from the bottom up.
+    #[must_use]
+    pub const fn vertical(value : f64, max : f64) -> Self {
+        Self::new(value, max).with_direction(Direction::Vertical)
+    }
+
+    #[must_use]
+    pub const fn with_direction(mut self, direction : Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_reversed(mut self, reversed : bool) -> Self {
+        self.reversed = reversed;
+        self
+    }
+
+    #[must_use]
+    pub fn with_label(mut self, label : impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    #[must_use]
+    pub const fn with_show_value(mut self, show_value : bool) -> Self {
+        self.show_value = show_value;
+        self
+    }
+
+    /// Draws the bar with `palette` once it is at or below `fraction` full.
+    #[must_use]
+    pub fn with_threshold(mut self, fraction : f64, palette : Palette) -> Self {
+        self.thresholds.push((fraction, palette));
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    pub const fn set(&mut self, value : f64, max : f64) {
+        self.value = value;
+        self.max = max;
+    }
+
+    /// How full the bar is, from `0.0` to `1.0`. A ratio that isn't finite,
+    /// such as that of a `NaN` value, counts as empty.
+    #[must_use]
+    pub fn ratio(&self) -> f64 {
+        let ratio = self.value / self.max;
+
+        if self.max > 0.0 && ratio.is_finite() {
+            ratio.clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// The palette of the lowest threshold the bar is at or below, or
+    /// `palette` if there is none.
+    #[must_use]
+    pub fn current_palette(&self, palette : Palette) -> Palette {
+        let ratio = self.ratio();
+
+        self.thresholds
+            .iter()
+            .filter(|(fraction, _)| ratio <= *fraction)
+            .min_by(|(lhs, _), (rhs, _)| lhs.total_cmp(rhs))
+            .map_or(palette, |(_, palette)| *palette)
+    }
+
+    /// The label with the value added, if it is shown.
+    fn text(&self) -> String {
+        match (self.show_value, self.label.is_empty()) {
+            (false, _) => self.label.clone(),
+            (true, true) => format!("{}/{}", self.value, self.max),
+            (true, false) => format!("{} {}/{}", self.label, self.value, self.max),
+        }
+    }
+}
+
+impl Widget for Gauge {
+    fn measure(&self, (width, height) : (i32, i32), _children : &[Node]) -> (i32, i32) {
+        match self.direction {
+            Direction::Horizontal => (width, height.min(1)),
+            Direction::Vertical => (width.min(1), height),
+        }
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = self.current_palette(self.palette.unwrap_or(ctx.palette));
+        let inverted = Palette::simple(palette.bg, palette.fg);
+        let rect = ctx.rect;
+
+        let vertical = self.direction == Direction::Vertical;
+        let length = if vertical { rect.height } else { rect.width };
+
+        if length <= 0 || rect.is_empty() {
+            return;
+        }
+
+        let half = Char437(match (vertical, self.reversed) {
+            (false, false) => 0xDD,
+            (false, true) => 0xDE,
+            (true, false) => 0xDC,
+            (true, true) => 0xDF,
+        });
+
+        // The cell of the bar `step` cells from where it fills from, on row or
+        // column `across`
+        let pos = |step : i32, across : i32| {
+            let along = if self.reversed == vertical {
+                step
+            } else {
+                length - 1 - step
+            };
+
+            if vertical {
+                (rect.x + across, rect.y + along)
+            } else {
+                (rect.x + along, rect.y + across)
+            }
+        };
+
+        let filled = self.ratio() * f64::from(length);
+        let thickness = if vertical { rect.width } else { rect.height };
+
+        for step in 0 .. length {
+            let covered = (filled - f64::from(step)).clamp(0.0, 1.0);
+            let glyph = fill_glyph(covered, half);
+
+            for across in 0 .. thickness {
+                console.set(pos(step, across), glyph, palette);
+            }
+        }
+
+        let text : Vec<Char437> = self
+            .text()
+            .chars()
+            .map(|chr| chr.to_cp437().unwrap_or(Char437(b'?')))
+            .take(usize::try_from(length).unwrap_or(0))
+            .collect();
+
+        if text.is_empty() {
+            return;
+        }
+
+        // Read left to right or top to bottom whichever way the bar fills
+        let text_length = i32::try_from(text.len()).unwrap_or(length);
+        let start = (length - text_length) / 2;
+
+        for (along, glyph) in (start ..).zip(text) {
+            let step = if self.reversed == vertical {
+                along
+            } else {
+                length - 1 - along
+            };
+            let covered = (filled - f64::from(step)).clamp(0.0, 1.0);
+
+            console.set(
+                pos(step, thickness / 2),
+                glyph,
+                if covered >= 0.5 { inverted } else { palette },
+            );
+        }
+    }
+}
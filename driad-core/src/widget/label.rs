@@ -0,0 +1,51 @@
+use crate::color::Palette;
+use crate::console::Console;
+use crate::text::TextLayout;
+use crate::widget::{DrawCtx, Node, Widget};
+
+/// A block of text laid out inside its area.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub text :    String,
+    pub layout :  TextLayout,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette : Option<Palette>,
+}
+
+impl Label {
+    #[must_use]
+    pub fn new(text : impl Into<String>) -> Self {
+        Self {
+            text : text.into(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub const fn with_layout(mut self, layout : TextLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+}
+
+impl Widget for Label {
+    fn measure(&self, (width, height) : (i32, i32), _children : &[Node]) -> (i32, i32) {
+        let (text_width, text_height) = self.layout.measure(&self.text, width);
+        (text_width, text_height.min(height))
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        console.print_text(
+            ctx.rect,
+            &self.text,
+            &self.layout,
+            self.palette.unwrap_or(ctx.palette),
+        );
+    }
+}
@@ -0,0 +1,488 @@
+use std::collections::BTreeSet;
+use std::iter;
+
+use log::warn;
+use mlua::Function;
+use sdl3::keyboard::Keycode;
+use sdl3::mouse::MouseButton;
+
+use crate::char::Char437;
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::input::Modifiers;
+use crate::mouse::MouseEventKind;
+use crate::widget::scrollbar::Scrollbar;
+use crate::widget::{DrawCtx, EventCtx, EventResult, MessageKind, Node, UiEvent, Widget};
+
+/// The letters given out as hotkeys by [`List::menu`], in order.
+const MENU_HOTKEYS : &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// An entry in a [`List`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListItem {
+    pub label :   String,
+    /// Picks the item when typed, shown before the label.
+    pub hotkey :  Option<char>,
+    /// Disabled items are drawn dimmed and can't be highlighted or picked.
+    pub enabled : bool,
+    /// Drawn with the palette of the list when `None`.
+    pub palette : Option<Palette>,
+}
+
+impl ListItem {
+    #[must_use]
+    pub fn new(label : impl Into<String>) -> Self {
+        Self {
+            label :   label.into(),
+            hotkey :  None,
+            enabled : true,
+            palette : None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_hotkey(mut self, hotkey : char) -> Self {
+        self.hotkey = Some(hotkey);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_enabled(mut self, enabled : bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+}
+
+impl<T : Into<String>> From<T> for ListItem {
+    fn from(label : T) -> Self {
+        Self::new(label)
+    }
+}
+
+/// How many items of a [`List`] can be selected.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SelectMode {
+    /// Picking an item selects it alone.
+    #[default]
+    Single,
+    /// Picking an item toggles whether it is selected, and enter confirms the
+    /// whole selection.
+    Multi,
+}
+
+/// A scrollable list of items, one per row, such as an inventory or a menu.
+///
+/// The arrow, page and home/end keys move the highlight past disabled items,
+/// while enter, clicking or typing an item's hotkey picks it. Picking an item
+/// in [`SelectMode::Single`] sends a [`MessageKind::Activated`], while in
+/// [`SelectMode::Multi`] it toggles the item and enter sends a
+/// [`MessageKind::Confirmed`] holding the whole selection. Every change to the
+/// selection also sends a [`MessageKind::SelectionChanged`]. Lua callbacks can
+/// be set to be called along with these messages.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct List {
+    items :       Vec<ListItem>,
+    highlighted : Option<usize>,
+    selected :    BTreeSet<usize>,
+    /// The first item shown.
+    scroll :      usize,
+    /// The number of rows the list was laid out with.
+    rows :        usize,
+
+    pub mode :    SelectMode,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette : Option<Palette>,
+
+    /// Called with the 1-based indices of the selected items when they
+    /// change.
+    on_select :  Option<Function>,
+    /// Called with the 1-based indices of the selected items when an item is
+    /// activated or the selection is confirmed.
+    on_confirm : Option<Function>,
+}
+
+impl List {
+    #[must_use]
+    pub fn new(items : impl IntoIterator<Item = impl Into<ListItem>>) -> Self {
+        let mut list = Self::default();
+        list.set_items(items);
+        list
+    }
+
+    /// A list with the hotkeys `a` to `z` and then `A` to `Z` given to its
+    /// items in order, as in the menus of most roguelikes.
+    #[must_use]
+    pub fn menu(items : impl IntoIterator<Item = impl Into<ListItem>>) -> Self {
+        Self::new(
+            items
+                .into_iter()
+                .map(Into::into)
+                .zip(MENU_HOTKEYS.chars().map(Some).chain(iter::repeat(None)))
+                .map(|(item, hotkey)| {
+                    ListItem {
+                        hotkey : item.hotkey.or(hotkey),
+                        ..item
+                    }
+                }),
+        )
+    }
+
+    #[must_use]
+    pub const fn with_mode(mut self, mode : SelectMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Calls a lua function with the 1-based indices of the selected items
+    /// whenever they change.
+    #[must_use]
+    pub fn with_on_select(mut self, callback : Function) -> Self {
+        self.on_select = Some(callback);
+        self
+    }
+
+    /// Calls a lua function with the 1-based indices of the selected items
+    /// when an item is activated in [`SelectMode::Single`] or enter confirms
+    /// the selection in [`SelectMode::Multi`].
+    #[must_use]
+    pub fn with_on_confirm(mut self, callback : Function) -> Self {
+        self.on_confirm = Some(callback);
+        self
+    }
+
+    #[must_use]
+    pub fn items(&self) -> &[ListItem] {
+        &self.items
+    }
+
+    /// Replaces the items, clearing the selection and highlighting the first
+    /// enabled item.
+    pub fn set_items(&mut self, items : impl IntoIterator<Item = impl Into<ListItem>>) {
+        self.items = items.into_iter().map(Into::into).collect();
+        self.selected.clear();
+        self.scroll = 0;
+        self.highlighted = self.items.iter().position(|item| item.enabled);
+    }
+
+    /// The item under the highlight.
+    #[must_use]
+    pub const fn highlighted(&self) -> Option<usize> {
+        self.highlighted
+    }
+
+    /// Moves the highlight to an item, if it is enabled.
+    pub fn highlight(&mut self, idx : usize) {
+        if self.items.get(idx).is_some_and(|item| item.enabled) {
+            self.highlighted = Some(idx);
+            self.scroll_to_highlight();
+        }
+    }
+
+    /// The selected items, in order.
+    #[must_use]
+    pub const fn selected(&self) -> &BTreeSet<usize> {
+        &self.selected
+    }
+
+    /// Selects or deselects an item, without sending a message.
+    pub fn set_selected(&mut self, idx : usize, selected : bool) {
+        if selected && idx < self.items.len() {
+            if self.mode == SelectMode::Single {
+                self.selected.clear();
+            }
+            self.selected.insert(idx);
+        } else {
+            self.selected.remove(&idx);
+        }
+    }
+
+    /// Picks an item as if it was clicked, returning whether it could be.
+    fn pick(&mut self, idx : usize, ctx : &mut EventCtx) -> bool {
+        if !self.items.get(idx).is_some_and(|item| item.enabled) {
+            return false;
+        }
+
+        self.highlight(idx);
+
+        match self.mode {
+            SelectMode::Single => {
+                self.set_selected(idx, true);
+                ctx.emit(MessageKind::Activated(idx));
+                self.call(self.on_confirm.as_ref());
+            },
+            SelectMode::Multi => {
+                let selected = !self.selected.contains(&idx);
+                self.set_selected(idx, selected);
+            },
+        }
+
+        ctx.emit(MessageKind::SelectionChanged(
+            self.selected.iter().copied().collect(),
+        ));
+        self.call(self.on_select.as_ref());
+
+        true
+    }
+
+    /// Calls a lua callback with the 1-based indices of the selected items.
+    fn call(&self, callback : Option<&Function>) {
+        let selected : Vec<usize> = self.selected.iter().map(|idx| idx + 1).collect();
+
+        if let Some(callback) = callback
+            && let Err(err) = callback.call::<()>(selected)
+        {
+            warn!("List callback failed: {err}");
+        }
+    }
+
+    /// Moves the highlight by `step` enabled items, stopping at the ends.
+    fn step(&mut self, step : isize) {
+        let enabled : Vec<usize> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.enabled)
+            .map(|(idx, _)| idx)
+            .collect();
+
+        let Some(last) = enabled.len().checked_sub(1) else {
+            return;
+        };
+
+        let current = self
+            .highlighted
+            .and_then(|highlighted| enabled.iter().position(|idx| *idx == highlighted));
+
+        let next = match current {
+            Some(current) => current.saturating_add_signed(step).min(last),
+            None if step < 0 => last,
+            None => 0,
+        };
+
+        self.highlight(enabled[next]);
+    }
+
+    fn scroll_to_highlight(&mut self) {
+        let Some(highlighted) = self.highlighted else {
+            return;
+        };
+
+        let rows = self.rows.max(1);
+
+        if highlighted < self.scroll {
+            self.scroll = highlighted;
+        } else if highlighted >= self.scroll + rows {
+            self.scroll = highlighted + 1 - rows;
+        }
+    }
+
+    const fn scrollbar(&self) -> Scrollbar {
+        Scrollbar::new(self.scroll, self.rows, self.items.len())
+    }
+
+    /// Scrolls the view without moving the highlight.
+    fn scroll_by(&mut self, lines : isize) {
+        let max = self.items.len().saturating_sub(self.rows);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max);
+    }
+
+    fn handle_key(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> bool {
+        let UiEvent::Key { key, modifiers, .. } = *event else {
+            return false;
+        };
+
+        let page = isize::try_from(self.rows.max(1)).unwrap_or(1);
+
+        if let Some(chr) = event.key_char()
+            && let Some(idx) = self.items.iter().position(|item| item.hotkey == Some(chr))
+        {
+            return self.pick(idx, ctx);
+        }
+
+        if modifiers != Modifiers::NONE {
+            return false;
+        }
+
+        match key {
+            Keycode::Up => self.step(-1),
+            Keycode::Down => self.step(1),
+            Keycode::PageUp => self.step(-page),
+            Keycode::PageDown => self.step(page),
+            Keycode::Home => self.step(isize::MIN),
+            Keycode::End => self.step(isize::MAX),
+            Keycode::Space if self.mode == SelectMode::Multi => {
+                return self
+                    .highlighted
+                    .is_some_and(|highlighted| self.pick(highlighted, ctx));
+            },
+            Keycode::Return | Keycode::KpEnter => {
+                let Some(highlighted) = self.highlighted else {
+                    return false;
+                };
+
+                match self.mode {
+                    SelectMode::Single => return self.pick(highlighted, ctx),
+                    SelectMode::Multi => {
+                        ctx.emit(MessageKind::Confirmed(
+                            self.selected.iter().copied().collect(),
+                        ));
+                        self.call(self.on_confirm.as_ref());
+                    },
+                }
+            },
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn handle_mouse(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> bool {
+        let UiEvent::Mouse(mouse) = event else {
+            return false;
+        };
+        let Some((x, y)) = event.cell() else {
+            return false;
+        };
+
+        let row = usize::try_from(y - ctx.rect.y).unwrap_or(0);
+        let scrollbar = self.scrollbar();
+
+        match mouse.kind {
+            MouseEventKind::Wheel { y: wheel, .. } => {
+                self.scroll_by(if wheel > 0.0 { -3 } else { 3 });
+                true
+            },
+            MouseEventKind::Pressed {
+                button: MouseButton::Left,
+                ..
+            } if scrollbar.is_needed() && x == ctx.rect.right() - 1 => {
+                self.scroll = scrollbar.offset_at(y - ctx.rect.y, ctx.rect.height);
+                true
+            },
+            MouseEventKind::Pressed {
+                button: MouseButton::Left,
+                ..
+            } => self.pick(self.scroll + row, ctx),
+            _ => false,
+        }
+    }
+}
+
+impl Widget for List {
+    fn measure(&self, (width, height) : (i32, i32), _children : &[Node]) -> (i32, i32) {
+        let checkbox = if self.mode == SelectMode::Multi { 4 } else { 0 };
+        let hotkey = if self.items.iter().any(|item| item.hotkey.is_some()) {
+            3
+        } else {
+            0
+        };
+
+        let label = self
+            .items
+            .iter()
+            .map(|item| item.label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let rows = self.items.len();
+        let scrollbar = usize::from(rows > usize::try_from(height).unwrap_or(0));
+
+        (
+            i32::try_from(checkbox + hotkey + label + scrollbar)
+                .unwrap_or(i32::MAX)
+                .min(width),
+            i32::try_from(rows).unwrap_or(i32::MAX).min(height),
+        )
+    }
+
+    fn layout(&mut self, rect : CellRect, _children : &mut [Node]) {
+        self.rows = usize::try_from(rect.height).unwrap_or(0);
+        self.scroll_by(0);
+        self.scroll_to_highlight();
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = self.palette.unwrap_or(ctx.palette);
+        let scrollbar = self.scrollbar();
+        let width = ctx.rect.width - i32::from(scrollbar.is_needed());
+
+        console.fill(ctx.rect, Char437(b' '), palette);
+
+        for (y, idx) in (ctx.rect.y .. ctx.rect.bottom()).zip(self.scroll .. self.items.len()) {
+            let item = &self.items[idx];
+            let mut item_palette = item.palette.unwrap_or(palette);
+
+            if !item.enabled {
+                item_palette.fg = item_palette.fg.dimmed();
+            } else if Some(idx) == self.highlighted && ctx.focused {
+                item_palette = item_palette.accent1();
+            } else if self.selected.contains(&idx) {
+                item_palette = item_palette.accent2();
+            }
+
+            let mut text = String::new();
+            if self.mode == SelectMode::Multi {
+                text.push_str(if self.selected.contains(&idx) {
+                    "[x] "
+                } else {
+                    "[ ] "
+                });
+            }
+            if let Some(hotkey) = item.hotkey {
+                text.push(hotkey);
+                text.push_str(") ");
+            }
+            text.push_str(&item.label);
+
+            let text : String = text
+                .chars()
+                .take(usize::try_from(width).unwrap_or(0))
+                .collect();
+
+            console.fill(
+                CellRect::new(ctx.rect.x, y, width, 1),
+                Char437(b' '),
+                item_palette,
+            );
+            console.print((ctx.rect.x, y), &text, item_palette);
+        }
+
+        if scrollbar.is_needed() {
+            scrollbar.draw(
+                console,
+                CellRect::new(ctx.rect.right() - 1, ctx.rect.y, 1, ctx.rect.height),
+                palette,
+            );
+        }
+    }
+
+    fn handle_event(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> EventResult {
+        let consumed = match event {
+            UiEvent::Key { .. } => self.handle_key(event, ctx),
+            UiEvent::Mouse(_) => self.handle_mouse(event, ctx),
+            UiEvent::Text(_) | UiEvent::Paste(_) => false,
+        };
+
+        if consumed {
+            EventResult::Consumed
+        } else {
+            EventResult::Ignored
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+}
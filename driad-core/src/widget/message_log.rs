@@ -0,0 +1,426 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::mem;
+
+use sdl3::keyboard::Keycode;
+
+use crate::char::Char437;
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::input::Modifiers;
+use crate::markup::{self, Span};
+use crate::mouse::MouseEventKind;
+use crate::widget::scrollbar::Scrollbar;
+use crate::widget::{DrawCtx, EventCtx, EventResult, Node, UiEvent, Widget};
+
+/// A glyph along with the colors it is drawn in.
+type StyledGlyph = (Char437, Palette);
+
+/// A message in a [`MessageLog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    /// Color markup, see [`markup::parse`]. Text with broken markup is shown
+    /// as is.
+    pub text :     String,
+    /// Shown in brackets before the text, such as the turn the message was
+    /// logged on.
+    pub stamp :    Option<String>,
+    /// Used to filter the log, see [`MessageLog::set_filter`].
+    pub category : Option<String>,
+    /// The colors the markup starts with, the palette of the log when `None`.
+    pub palette :  Option<Palette>,
+    /// How many times the message was logged in a row.
+    pub count :    u32,
+}
+
+impl LogEntry {
+    #[must_use]
+    pub fn new(text : impl Into<String>) -> Self {
+        Self {
+            text :     text.into(),
+            stamp :    None,
+            category : None,
+            palette :  None,
+            count :    1,
+        }
+    }
+
+    /// Stamps the entry with a turn number.
+    #[must_use]
+    pub fn with_turn(self, turn : u64) -> Self {
+        self.with_stamp(turn.to_string())
+    }
+
+    #[must_use]
+    pub fn with_stamp(mut self, stamp : impl Into<String>) -> Self {
+        self.stamp = Some(stamp.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_category(mut self, category : impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Whether `other` is the same message, so it can be collapsed into this
+    /// one.
+    fn repeats(&self, other : &Self) -> bool {
+        self.text == other.text && self.category == other.category && self.palette == other.palette
+    }
+
+    /// The entry as spans, with its stamp before it and its count after it.
+    fn spans(&self, base : Palette) -> Vec<Span> {
+        let base = self.palette.unwrap_or(base);
+
+        let mut spans = Vec::new();
+
+        if let Some(stamp) = &self.stamp {
+            spans.push(Span::new(format!("[{stamp}] "), base.accent2()));
+        }
+
+        spans.extend(
+            markup::parse(&self.text, base)
+                .unwrap_or_else(|_| vec![Span::new(self.text.clone(), base)]),
+        );
+
+        if self.count > 1 {
+            spans.push(Span::new(format!(" x{}", self.count), base.accent2()));
+        }
+
+        spans
+    }
+}
+
+impl<T : Into<String>> From<T> for LogEntry {
+    fn from(text : T) -> Self {
+        Self::new(text)
+    }
+}
+
+/// Breaks styled glyphs into lines between words, as [`crate::text::wrap`]
+/// does for plain text.
+fn wrap_glyphs(glyphs : &[StyledGlyph], width : usize) -> Vec<Vec<StyledGlyph>> {
+    let width = width.max(1);
+
+    let mut lines = Vec::new();
+    let mut line : Vec<StyledGlyph> = Vec::new();
+
+    for word in glyphs.split(|(glyph, _)| *glyph == Char437(b' ')) {
+        if !line.is_empty() {
+            if line.len() + 1 + word.len() <= width {
+                let palette = line
+                    .last()
+                    .map_or_else(Palette::default, |(_, palette)| *palette);
+                line.push((Char437(b' '), palette));
+            } else {
+                lines.push(mem::take(&mut line));
+            }
+        }
+
+        let mut rest = word;
+        while rest.len() > width {
+            let (head, tail) = rest.split_at(width);
+            lines.push(head.to_vec());
+            rest = tail;
+        }
+
+        line.extend_from_slice(rest);
+    }
+
+    lines.push(line);
+    lines
+}
+
+/// A scrolling log of messages, newest at the bottom, such as the message
+/// area of a roguelike.
+///
+/// A message logged again right after itself is collapsed into the first,
+/// shown as `You hear a noise x3`. Lines wrap to the width of the log. The
+/// arrow, page and home/end keys while the log has focus and the mouse wheel
+/// scroll back through older messages, and the log follows new messages again
+/// once scrolled to the bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageLog {
+    entries :     VecDeque<LogEntry>,
+    /// The number of lines each entry wraps to, wrapped again only when the
+    /// width changes.
+    line_counts : VecDeque<usize>,
+    /// Only entries in these categories are shown, along with entries without
+    /// a category. Every entry is shown when `None`.
+    filter :      Option<BTreeSet<String>>,
+    /// How many lines the log is scrolled back from the newest.
+    scroll :      usize,
+    /// The size of the log as of the last layout.
+    size :        (usize, usize),
+    focused :     bool,
+
+    /// The most entries kept, older entries being dropped.
+    pub capacity : usize,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette :  Option<Palette>,
+}
+
+impl Default for MessageLog {
+    fn default() -> Self {
+        Self {
+            entries :     VecDeque::new(),
+            line_counts : VecDeque::new(),
+            filter :      None,
+            scroll :      0,
+            size :        (0, 0),
+            focused :     false,
+            capacity :    500,
+            palette :     None,
+        }
+    }
+}
+
+impl MessageLog {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub const fn with_capacity(mut self, capacity : usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Logs a message, collapsing it into the newest entry if it repeats it.
+    /// The collapsed entry takes the stamp of the repeat, if it has one.
+    pub fn push(&mut self, entry : impl Into<LogEntry>) {
+        let entry = entry.into();
+        let shown = self.is_shown(&entry);
+        let before = match self.entries.back_mut() {
+            Some(last) if last.repeats(&entry) => {
+                last.count += 1;
+                if entry.stamp.is_some() {
+                    last.stamp = entry.stamp;
+                }
+                self.line_counts.pop_back().unwrap_or_default()
+            },
+            _ => {
+                self.entries.push_back(entry);
+                0
+            },
+        };
+
+        let lines = self.entries.back().map_or(0, |last| self.entry_lines(last));
+        self.line_counts.push_back(lines);
+
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+            self.line_counts.pop_front();
+        }
+
+        // Keep showing the same lines while scrolled back
+        if shown && self.scroll > 0 {
+            self.scroll += lines.saturating_sub(before);
+        }
+
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.line_counts.clear();
+        self.scroll = 0;
+    }
+
+    #[must_use]
+    pub const fn entries(&self) -> &VecDeque<LogEntry> {
+        &self.entries
+    }
+
+    /// Only shows entries in `categories`, along with entries without a
+    /// category, or every entry when `None`.
+    pub fn set_filter(&mut self, categories : Option<impl IntoIterator<Item = impl Into<String>>>) {
+        self.filter = categories.map(|categories| categories.into_iter().map(Into::into).collect());
+        self.scroll = 0;
+    }
+
+    #[must_use]
+    pub const fn filter(&self) -> Option<&BTreeSet<String>> {
+        self.filter.as_ref()
+    }
+
+    /// Whether an entry passes the filter.
+    fn is_shown(&self, entry : &LogEntry) -> bool {
+        match (&self.filter, &entry.category) {
+            (Some(filter), Some(category)) => filter.contains(category),
+            _ => true,
+        }
+    }
+
+    /// The width lines wrap to, leaving room for the scrollbar.
+    const fn wrap_width(&self) -> usize {
+        self.size.0.saturating_sub(1)
+    }
+
+    fn lines(&self, entry : &LogEntry, base : Palette) -> Vec<Vec<StyledGlyph>> {
+        let glyphs : Vec<StyledGlyph> = markup::glyphs(&entry.spans(base)).collect();
+        wrap_glyphs(&glyphs, self.wrap_width())
+    }
+
+    /// The number of lines an entry wraps to.
+    fn entry_lines(&self, entry : &LogEntry) -> usize {
+        self.lines(entry, Palette::default()).len()
+    }
+
+    /// The number of lines every shown entry wraps to.
+    fn total_lines(&self) -> usize {
+        self.entries
+            .iter()
+            .zip(&self.line_counts)
+            .filter(|(entry, _)| self.is_shown(entry))
+            .map(|(_, lines)| lines)
+            .sum()
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.total_lines().saturating_sub(self.size.1)
+    }
+
+    /// Scrolls back towards older messages by `lines`, or forwards if
+    /// negative.
+    pub fn scroll_by(&mut self, lines : isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(lines)
+            .min(self.max_scroll());
+    }
+
+    /// How many lines the log is scrolled back from the newest message.
+    #[must_use]
+    pub const fn scroll(&self) -> usize {
+        self.scroll
+    }
+
+    /// Scrolls with a key while the log has focus, returning whether the log
+    /// scrolled.
+    fn handle_key(&mut self, key : Keycode, modifiers : Modifiers) -> bool {
+        if !self.focused || modifiers != Modifiers::NONE {
+            return false;
+        }
+
+        let page = isize::try_from(self.size.1.max(1)).unwrap_or(1);
+        let scroll = self.scroll;
+
+        match key {
+            Keycode::Up => self.scroll_by(1),
+            Keycode::Down => self.scroll_by(-1),
+            Keycode::PageUp => self.scroll_by(page),
+            Keycode::PageDown => self.scroll_by(-page),
+            Keycode::Home => self.scroll_by(isize::MAX),
+            Keycode::End => self.scroll = 0,
+            _ => return false,
+        }
+
+        self.scroll != scroll
+    }
+}
+
+impl Widget for MessageLog {
+    fn layout(&mut self, rect : CellRect, _children : &mut [Node]) {
+        let width = self.size.0;
+        self.size = (
+            usize::try_from(rect.width).unwrap_or(0),
+            usize::try_from(rect.height).unwrap_or(0),
+        );
+
+        if self.size.0 != width {
+            self.line_counts = self
+                .entries
+                .iter()
+                .map(|entry| self.entry_lines(entry))
+                .collect();
+        }
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = self.palette.unwrap_or(ctx.palette);
+        let height = self.size.1;
+
+        console.fill(ctx.rect, Char437(b' '), palette);
+
+        // Wrap from the newest entry back, only as far as is shown
+        let mut lines : Vec<Vec<StyledGlyph>> = Vec::new();
+        for entry in self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| self.is_shown(entry))
+        {
+            if lines.len() >= height + self.scroll {
+                break;
+            }
+
+            lines.extend(self.lines(entry, palette).into_iter().rev());
+        }
+
+        let shown = lines.iter().skip(self.scroll).take(height).rev();
+        let top = ctx.rect.bottom() - i32::try_from(shown.len()).unwrap_or(0);
+
+        for (y, line) in (top ..).zip(shown) {
+            for (x, (glyph, glyph_palette)) in (ctx.rect.x ..).zip(line) {
+                console.set((x, y), *glyph, *glyph_palette);
+            }
+        }
+
+        let total = self.total_lines();
+        let scrollbar = Scrollbar::new(total.saturating_sub(height + self.scroll), height, total);
+
+        if scrollbar.is_needed() {
+            scrollbar.draw(
+                console,
+                CellRect::new(ctx.rect.right() - 1, ctx.rect.y, 1, ctx.rect.height),
+                palette,
+            );
+        }
+    }
+
+    fn handle_event(&mut self, event : &UiEvent, _ctx : &mut EventCtx) -> EventResult {
+        let consumed = match event {
+            UiEvent::Key { key, modifiers, .. } => self.handle_key(*key, *modifiers),
+            UiEvent::Mouse(mouse) => {
+                match mouse.kind {
+                    MouseEventKind::Wheel { y, .. } => {
+                        let scroll = self.scroll;
+                        self.scroll_by(if y > 0.0 { 3 } else { -3 });
+                        self.scroll != scroll
+                    },
+                    _ => false,
+                }
+            },
+            UiEvent::Text(_) | UiEvent::Paste(_) => false,
+        };
+
+        if consumed {
+            EventResult::Consumed
+        } else {
+            EventResult::Ignored
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn focus_changed(&mut self, focused : bool) {
+        self.focused = focused;
+    }
+}
@@ -0,0 +1,104 @@
+use crate::border::BoxStyle;
+use crate::char::Char437;
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::widget::{DrawCtx, Node, Widget};
+
+/// A filled box with a border and an optional title, holding its children
+/// inside the border.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Panel {
+    pub title :   Option<String>,
+    pub style :   BoxStyle,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette : Option<Palette>,
+    /// Empty cells between the border and the children.
+    pub padding : i32,
+}
+
+impl Panel {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_title(mut self, title : impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn with_style(mut self, style : BoxStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_padding(mut self, padding : i32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// The area inside the border and padding of a panel covering `rect`.
+    #[must_use]
+    pub fn inner(&self, rect : CellRect) -> CellRect {
+        let inset = 1 + self.padding;
+
+        CellRect::new(
+            rect.x + inset,
+            rect.y + inset,
+            (rect.width - 2 * inset).max(0),
+            (rect.height - 2 * inset).max(0),
+        )
+    }
+}
+
+impl Widget for Panel {
+    fn measure(&self, (width, height) : (i32, i32), children : &[Node]) -> (i32, i32) {
+        let inset = 2 * (1 + self.padding);
+
+        let (inner_width, inner_height) = children
+            .iter()
+            .map(|child| child.measure((width - inset, height - inset)))
+            .fold((0, 0), |(w, h), (child_w, child_h)| {
+                (w.max(child_w), h.max(child_h))
+            });
+
+        let title = self.title.as_ref().map_or(0, |title| {
+            i32::try_from(title.chars().count()).unwrap_or(i32::MAX)
+        });
+
+        (
+            (inner_width + inset)
+                .max(title.saturating_add(6))
+                .min(width),
+            (inner_height + inset).min(height),
+        )
+    }
+
+    fn layout(&mut self, rect : CellRect, children : &mut [Node]) {
+        let inner = self.inner(rect);
+
+        for child in children {
+            child.layout(inner);
+        }
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = self.palette.unwrap_or(ctx.palette);
+
+        console.fill(ctx.rect, Char437(b' '), palette);
+
+        match &self.title {
+            Some(title) => console.draw_titled_box(ctx.rect, self.style, palette, title),
+            None => console.draw_box(ctx.rect, self.style, palette),
+        }
+    }
+}
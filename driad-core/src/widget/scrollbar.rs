@@ -0,0 +1,83 @@
+use crate::char::Shade;
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+
+/// How far a view is scrolled through its content, drawn as a bar of shade
+/// glyphs. The track is drawn with `░` and the thumb with `█`, its ends with
+/// `▒` or `▓` when they only partly cover a cell.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scrollbar {
+    /// The first line shown.
+    pub offset :  usize,
+    /// The number of lines shown at once.
+    pub visible : usize,
+    /// The number of lines there are.
+    pub total :   usize,
+}
+
+impl Scrollbar {
+    #[must_use]
+    pub const fn new(offset : usize, visible : usize, total : usize) -> Self {
+        Self {
+            offset,
+            visible,
+            total,
+        }
+    }
+
+    /// Whether there is more content than fits, so the bar is worth drawing.
+    #[must_use]
+    pub const fn is_needed(&self) -> bool {
+        self.total > self.visible
+    }
+
+    /// The offset to scroll to for a click on cell `pos` of a bar `length`
+    /// cells long.
+    #[must_use]
+    pub fn offset_at(&self, pos : i32, length : i32) -> usize {
+        let pos = usize::try_from(pos).unwrap_or(0);
+        let length = usize::try_from(length).unwrap_or(0).max(1);
+
+        (pos * self.total / length).min(self.total.saturating_sub(self.visible))
+    }
+
+    /// Draws the bar down `rect`, or across it if it is wider than it is tall.
+    pub fn draw(&self, console : &mut Console, rect : CellRect, palette : Palette) {
+        let vertical = rect.height >= rect.width;
+        let length = if vertical { rect.height } else { rect.width };
+
+        if length <= 0 || self.total == 0 {
+            return;
+        }
+
+        let lines = |lines : usize| f64::from(u32::try_from(lines).unwrap_or(u32::MAX));
+
+        // In cells of the bar, to cover partial cells at the ends of the thumb
+        let scale = f64::from(length) / lines(self.total);
+        let start = lines(self.offset) * scale;
+        let end = lines((self.offset + self.visible).min(self.total)) * scale;
+
+        for idx in 0 .. length {
+            let cell = f64::from(idx);
+            let covered = (end.min(cell + 1.0) - start.max(cell)).max(0.0);
+
+            let shade = if covered <= 0.0 {
+                Shade::Light
+            } else if covered < 0.5 {
+                Shade::Medium
+            } else if covered < 1.0 {
+                Shade::Dark
+            } else {
+                Shade::Full
+            };
+
+            let pos = if vertical {
+                (rect.x, rect.y + idx)
+            } else {
+                (rect.x + idx, rect.y)
+            };
+
+            console.set(pos, shade.glyph(), palette);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::console::CellRect;
+use crate::layout::{Layout, Margin};
+use crate::widget::{Node, Widget};
+
+/// Lays its children out in the segments of a [`Layout`], the first child in
+/// the first segment and so on. Children without a segment are given an empty
+/// rect.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Split {
+    pub layout :  Layout,
+    /// Empty cells around the inside of each segment.
+    pub padding : Margin,
+}
+
+impl Split {
+    #[must_use]
+    pub fn new(layout : Layout) -> Self {
+        Self {
+            layout,
+            padding : Margin::default(),
+        }
+    }
+
+    #[must_use]
+    pub const fn with_padding(mut self, padding : Margin) -> Self {
+        self.padding = padding;
+        self
+    }
+}
+
+impl Widget for Split {
+    fn layout(&mut self, rect : CellRect, children : &mut [Node]) {
+        let mut segments = self.layout.split(rect).into_iter();
+
+        for child in children {
+            let segment = segments.next().map_or_else(
+                || CellRect::new(rect.x, rect.y, 0, 0),
+                |segment| segment.inset(self.padding),
+            );
+
+            child.layout(segment);
+        }
+    }
+}
@@ -0,0 +1,546 @@
+use std::cmp::Ordering;
+
+use sdl3::keyboard::Keycode;
+use sdl3::mouse::MouseButton;
+
+use crate::border::{BoxStyle, join_lines};
+use crate::char::Char437;
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::input::Modifiers;
+use crate::layout::{Constraint, Layout};
+use crate::mouse::MouseEventKind;
+use crate::text::{HAlign, TextLayout, VAlign};
+use crate::widget::scrollbar::Scrollbar;
+use crate::widget::{DrawCtx, EventCtx, EventResult, MessageKind, Node, UiEvent, Widget};
+
+/// `▲`, shown after the title of a column sorted in ascending order.
+const ASCENDING : Char437 = Char437(30);
+
+/// `▼`, shown after the title of a column sorted in descending order.
+const DESCENDING : Char437 = Char437(31);
+
+/// The rows above the body, the titles and the line under them.
+const HEADER_HEIGHT : i32 = 2;
+
+/// A column of a [`Table`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub title :    String,
+    /// How wide the column is, shared out as in a [`Layout`].
+    pub width :    Constraint,
+    pub align :    HAlign,
+    /// Whether the rows can be sorted by this column.
+    pub sortable : bool,
+}
+
+impl Column {
+    #[must_use]
+    pub fn new(title : impl Into<String>, width : Constraint) -> Self {
+        Self {
+            title : title.into(),
+            width,
+            align : HAlign::Left,
+            sortable : true,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_align(mut self, align : HAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_sortable(mut self, sortable : bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+}
+
+/// The text in one cell of a [`Table`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TableCell {
+    pub text :    String,
+    /// Drawn with the palette of the table when `None`.
+    pub palette : Option<Palette>,
+}
+
+impl TableCell {
+    #[must_use]
+    pub fn new(text : impl Into<String>) -> Self {
+        Self {
+            text :    text.into(),
+            palette : None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+}
+
+impl<T : Into<String>> From<T> for TableCell {
+    fn from(text : T) -> Self {
+        Self::new(text)
+    }
+}
+
+/// Compares cells as numbers if both are, and as text otherwise.
+fn compare_cells(a : &TableCell, b : &TableCell) -> Ordering {
+    match (a.text.trim().parse::<f64>(), b.text.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.total_cmp(&b),
+        _ => a.text.cmp(&b.text),
+    }
+}
+
+/// The direction a [`Table`] is sorted in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+/// Rows of text in columns under a header, such as a stats or item comparison
+/// screen.
+///
+/// The arrow, page and home/end keys move the selected row and enter sends a
+/// [`MessageKind::Activated`] holding its index. Clicking a column title, or
+/// typing its number, sorts the rows by that column, then reverses the order.
+/// Every change to the selected row sends a [`MessageKind::SelectionChanged`].
+/// Row indices always refer to the order the rows were given in, not the
+/// sorted order.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Table {
+    columns :  Vec<Column>,
+    rows :     Vec<Vec<TableCell>>,
+    /// The rows in the order they are shown.
+    order :    Vec<usize>,
+    selected : Option<usize>,
+    sort :     Option<(usize, SortOrder)>,
+    /// The first row shown, as a position in `order`.
+    scroll :   usize,
+    /// The number of rows the body was laid out with.
+    visible :  usize,
+
+    /// The lines between the columns and under the header.
+    pub style :   BoxStyle,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette : Option<Palette>,
+}
+
+impl Table {
+    #[must_use]
+    pub fn new(columns : impl IntoIterator<Item = Column>) -> Self {
+        Self {
+            columns : columns.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_row(mut self, row : impl IntoIterator<Item = impl Into<TableCell>>) -> Self {
+        self.push_row(row);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_style(mut self, style : BoxStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    #[must_use]
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    #[must_use]
+    pub fn rows(&self) -> &[Vec<TableCell>] {
+        &self.rows
+    }
+
+    /// Adds a row, keeping the table sorted. Returns the index of the row.
+    pub fn push_row(&mut self, row : impl IntoIterator<Item = impl Into<TableCell>>) -> usize {
+        self.rows.push(row.into_iter().map(Into::into).collect());
+        self.order.push(self.rows.len() - 1);
+        self.apply_sort();
+
+        if self.selected.is_none() {
+            self.selected = Some(0);
+        }
+
+        self.rows.len() - 1
+    }
+
+    /// Removes every row.
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.order.clear();
+        self.selected = None;
+        self.scroll = 0;
+    }
+
+    /// A cell, for changing its text or palette.
+    pub fn cell_mut(&mut self, row : usize, column : usize) -> Option<&mut TableCell> {
+        self.rows.get_mut(row)?.get_mut(column)
+    }
+
+    /// The selected row.
+    #[must_use]
+    pub const fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn select(&mut self, row : usize) {
+        if row < self.rows.len() {
+            self.selected = Some(row);
+            self.scroll_to_selected();
+        }
+    }
+
+    /// The column and direction the rows are sorted by.
+    #[must_use]
+    pub const fn sort(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    /// Sorts the rows by a column, or shows them in the order they were given
+    /// in when `None`.
+    pub fn sort_by(&mut self, sort : Option<(usize, SortOrder)>) {
+        self.sort = sort;
+        self.apply_sort();
+    }
+
+    fn apply_sort(&mut self) {
+        match self.sort {
+            Some((column, order)) => {
+                let rows = &self.rows;
+                let cell = |row : usize| rows[row].get(column);
+
+                self.order.sort_by(|a, b| {
+                    let ordering = match (cell(*a), cell(*b)) {
+                        (Some(a), Some(b)) => compare_cells(a, b),
+                        (a, b) => a.is_some().cmp(&b.is_some()),
+                    };
+
+                    match order {
+                        SortOrder::Ascending => ordering,
+                        SortOrder::Descending => ordering.reverse(),
+                    }
+                });
+            },
+            None => self.order.sort_unstable(),
+        }
+
+        self.scroll_to_selected();
+    }
+
+    /// Sorts by a column, reversing the order if it is already sorted by it.
+    fn toggle_sort(&mut self, column : usize) -> bool {
+        if !self
+            .columns
+            .get(column)
+            .is_some_and(|column| column.sortable)
+        {
+            return false;
+        }
+
+        let order = match self.sort {
+            Some((sorted, SortOrder::Ascending)) if sorted == column => SortOrder::Descending,
+            _ => SortOrder::Ascending,
+        };
+
+        self.sort_by(Some((column, order)));
+        true
+    }
+
+    /// The position of the selected row in the shown order.
+    fn selected_position(&self) -> Option<usize> {
+        let selected = self.selected?;
+        self.order.iter().position(|row| *row == selected)
+    }
+
+    fn scroll_to_selected(&mut self) {
+        let Some(position) = self.selected_position() else {
+            return;
+        };
+
+        let visible = self.visible.max(1);
+
+        if position < self.scroll {
+            self.scroll = position;
+        } else if position >= self.scroll + visible {
+            self.scroll = position + 1 - visible;
+        }
+    }
+
+    fn scroll_by(&mut self, lines : isize) {
+        let max = self.rows.len().saturating_sub(self.visible);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max);
+    }
+
+    /// Moves the selection by `step` rows in the shown order, stopping at the
+    /// ends.
+    fn step(&mut self, step : isize, ctx : &mut EventCtx) {
+        let Some(last) = self.order.len().checked_sub(1) else {
+            return;
+        };
+
+        let position = self
+            .selected_position()
+            .map_or(0, |position| position.saturating_add_signed(step).min(last));
+
+        self.select_position(position, ctx);
+    }
+
+    fn select_position(&mut self, position : usize, ctx : &mut EventCtx) -> bool {
+        let Some(row) = self.order.get(position).copied() else {
+            return false;
+        };
+
+        if self.selected != Some(row) {
+            self.select(row);
+            ctx.emit(MessageKind::SelectionChanged(vec![row]));
+        }
+
+        true
+    }
+
+    const fn scrollbar(&self) -> Scrollbar {
+        Scrollbar::new(self.scroll, self.visible, self.rows.len())
+    }
+
+    /// The rect of each column inside `rect`, leaving room for the scrollbar
+    /// and a line between neighbouring columns.
+    fn column_rects(&self, rect : CellRect) -> Vec<CellRect> {
+        let scrollbar = i32::from(self.scrollbar().is_needed());
+
+        Layout::horizontal(self.columns.iter().map(|column| column.width))
+            .with_spacing(1)
+            .split(CellRect::new(
+                rect.x,
+                rect.y,
+                rect.width - scrollbar,
+                rect.height,
+            ))
+    }
+
+    fn handle_key(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> bool {
+        let UiEvent::Key { key, modifiers, .. } = *event else {
+            return false;
+        };
+
+        if modifiers != Modifiers::NONE {
+            return false;
+        }
+
+        let page = isize::try_from(self.visible.max(1)).unwrap_or(1);
+
+        if let Some(column) = event
+            .key_char()
+            .and_then(|chr| chr.to_digit(10))
+            .and_then(|digit| usize::try_from(digit).ok()?.checked_sub(1))
+        {
+            return self.toggle_sort(column);
+        }
+
+        match key {
+            Keycode::Up => self.step(-1, ctx),
+            Keycode::Down => self.step(1, ctx),
+            Keycode::PageUp => self.step(-page, ctx),
+            Keycode::PageDown => self.step(page, ctx),
+            Keycode::Home => self.step(isize::MIN, ctx),
+            Keycode::End => self.step(isize::MAX, ctx),
+            Keycode::Return | Keycode::KpEnter => {
+                let Some(selected) = self.selected else {
+                    return false;
+                };
+                ctx.emit(MessageKind::Activated(selected));
+            },
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn handle_mouse(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> bool {
+        let UiEvent::Mouse(mouse) = event else {
+            return false;
+        };
+        let Some((x, y)) = event.cell() else {
+            return false;
+        };
+
+        let scrollbar = self.scrollbar();
+        let body_y = ctx.rect.y + HEADER_HEIGHT;
+
+        match mouse.kind {
+            MouseEventKind::Wheel { y: wheel, .. } => {
+                self.scroll_by(if wheel > 0.0 { -3 } else { 3 });
+                true
+            },
+            MouseEventKind::Pressed {
+                button: MouseButton::Left,
+                ..
+            } if y == ctx.rect.y => {
+                self.column_rects(ctx.rect)
+                    .iter()
+                    .position(|rect| x >= rect.x && x < rect.right())
+                    .is_some_and(|column| self.toggle_sort(column))
+            },
+            MouseEventKind::Pressed {
+                button: MouseButton::Left,
+                ..
+            } if y >= body_y => {
+                if scrollbar.is_needed() && x == ctx.rect.right() - 1 {
+                    self.scroll = scrollbar.offset_at(y - body_y, ctx.rect.height - HEADER_HEIGHT);
+                    return true;
+                }
+
+                let position = self.scroll + usize::try_from(y - body_y).unwrap_or(0);
+                self.select_position(position, ctx)
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Widget for Table {
+    fn measure(&self, (width, height) : (i32, i32), _children : &[Node]) -> (i32, i32) {
+        let rows = i32::try_from(self.rows.len()).unwrap_or(i32::MAX);
+        (width, rows.saturating_add(HEADER_HEIGHT).min(height))
+    }
+
+    fn layout(&mut self, rect : CellRect, _children : &mut [Node]) {
+        self.visible = usize::try_from(rect.height - HEADER_HEIGHT).unwrap_or(0);
+        self.scroll_by(0);
+        self.scroll_to_selected();
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = self.palette.unwrap_or(ctx.palette);
+        let chars = self.style.chars();
+        let crossing = join_lines(chars.top, chars.left).unwrap_or(chars.top);
+
+        console.fill(ctx.rect, Char437(b' '), palette);
+
+        let columns = self.column_rects(ctx.rect);
+        let cell_layout = TextLayout::new().with_wrap(false);
+
+        // The header, with the sort order after the title of the sorted column
+        for (idx, (column, rect)) in self.columns.iter().zip(&columns).enumerate() {
+            let title = CellRect::new(rect.x, rect.y, rect.width, 1);
+            let block = console.print_text(
+                title,
+                &column.title,
+                &cell_layout.aligned(column.align, VAlign::Top),
+                palette.accent2(),
+            );
+
+            if let Some((sorted, order)) = self.sort
+                && sorted == idx
+            {
+                let arrow = match order {
+                    SortOrder::Ascending => ASCENDING,
+                    SortOrder::Descending => DESCENDING,
+                };
+                let x = (block.bounds.right()).min(rect.right() - 1);
+                console.set((x, rect.y), arrow, palette.accent2());
+            }
+        }
+
+        let line_y = ctx.rect.y + 1;
+        if line_y < ctx.rect.bottom() {
+            console.fill(
+                CellRect::new(ctx.rect.x, line_y, ctx.rect.width, 1),
+                chars.top,
+                palette,
+            );
+        }
+
+        // The lines between columns, crossing the line under the header
+        for rect in columns.iter().skip(1) {
+            let x = rect.x - 1;
+
+            for y in ctx.rect.y .. ctx.rect.bottom() {
+                let glyph = if y == line_y { crossing } else { chars.left };
+                console.set((x, y), glyph, palette);
+            }
+        }
+
+        let body = (ctx.rect.y + HEADER_HEIGHT) .. ctx.rect.bottom();
+
+        for (y, row) in body.zip(self.order.iter().skip(self.scroll)) {
+            let row_palette = if Some(*row) == self.selected {
+                Some(if ctx.focused {
+                    palette.accent1()
+                } else {
+                    palette.accent2()
+                })
+            } else {
+                None
+            };
+
+            if let Some(row_palette) = row_palette {
+                for rect in &columns {
+                    console.fill(
+                        CellRect::new(rect.x, y, rect.width, 1),
+                        Char437(b' '),
+                        row_palette,
+                    );
+                }
+            }
+
+            for ((cell, column), rect) in self.rows[*row].iter().zip(&self.columns).zip(&columns) {
+                console.print_text(
+                    CellRect::new(rect.x, y, rect.width, 1),
+                    &cell.text,
+                    &cell_layout.aligned(column.align, VAlign::Top),
+                    row_palette.or(cell.palette).unwrap_or(palette),
+                );
+            }
+        }
+
+        let scrollbar = self.scrollbar();
+        if scrollbar.is_needed() {
+            scrollbar.draw(
+                console,
+                CellRect::new(
+                    ctx.rect.right() - 1,
+                    ctx.rect.y + HEADER_HEIGHT,
+                    1,
+                    ctx.rect.height - HEADER_HEIGHT,
+                ),
+                palette,
+            );
+        }
+    }
+
+    fn handle_event(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> EventResult {
+        let consumed = match event {
+            UiEvent::Key { .. } => self.handle_key(event, ctx),
+            UiEvent::Mouse(_) => self.handle_mouse(event, ctx),
+            UiEvent::Text(_) | UiEvent::Paste(_) => false,
+        };
+
+        if consumed {
+            EventResult::Consumed
+        } else {
+            EventResult::Ignored
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+}
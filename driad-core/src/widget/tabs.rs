@@ -0,0 +1,281 @@
+use sdl3::keyboard::Keycode;
+use sdl3::mouse::MouseButton;
+
+use crate::border::BoxStyle;
+use crate::char::{CP437CharExt, Char437};
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::input::Modifiers;
+use crate::mouse::MouseEventKind;
+use crate::widget::{DrawCtx, EventCtx, EventResult, MessageKind, Node, UiEvent, Widget};
+
+/// A tab of a [`Tabs`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tab {
+    pub title :    String,
+    /// Switches to the tab when typed with alt held, and is drawn with the
+    /// second accent of the palette in the title.
+    pub shortcut : Option<char>,
+}
+
+impl Tab {
+    #[must_use]
+    pub fn new(title : impl Into<String>) -> Self {
+        Self {
+            title :    title.into(),
+            shortcut : None,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_shortcut(mut self, shortcut : char) -> Self {
+        self.shortcut = Some(shortcut);
+        self
+    }
+}
+
+impl<T : Into<String>> From<T> for Tab {
+    fn from(title : T) -> Self {
+        Self::new(title)
+    }
+}
+
+/// A row of tabs over a panel, showing one child at a time, such as the pages
+/// of a character sheet. The first child belongs to the first tab and so on,
+/// and the children of the other tabs are hidden.
+///
+/// Clicking a title switches tabs, as do ctrl+tab and ctrl+shift+tab, ctrl+page
+/// up/down and alt with a tab's number or shortcut while focus is inside the
+/// tabs, and the left and right keys while the tabs themselves have focus.
+/// Switching sends a [`MessageKind::TabChanged`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Tabs {
+    items :    Vec<Tab>,
+    selected : usize,
+    focused :  bool,
+    /// The titles as of the last layout.
+    titles :   Vec<CellRect>,
+
+    /// Used for the line under the titles.
+    pub style :   BoxStyle,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette : Option<Palette>,
+}
+
+impl Tabs {
+    #[must_use]
+    pub fn new(tabs : impl IntoIterator<Item = impl Into<Tab>>) -> Self {
+        Self {
+            items : tabs.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub const fn with_style(mut self, style : BoxStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    #[must_use]
+    pub fn tabs(&self) -> &[Tab] {
+        &self.items
+    }
+
+    /// The tab being shown.
+    #[must_use]
+    pub const fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Shows a tab, without sending a message.
+    pub const fn select(&mut self, idx : usize) {
+        if idx < self.items.len() {
+            self.selected = idx;
+        }
+    }
+
+    /// Shows a tab, sending a message if it changed. Returns whether there is
+    /// such a tab.
+    fn switch(&mut self, idx : usize, ctx : &mut EventCtx) -> bool {
+        if idx >= self.items.len() {
+            return false;
+        }
+
+        if idx != self.selected {
+            self.selected = idx;
+            ctx.emit(MessageKind::TabChanged(idx));
+        }
+
+        true
+    }
+
+    /// Shows the tab `step` tabs away, wrapping around at the ends.
+    fn cycle(&mut self, step : isize, ctx : &mut EventCtx) -> bool {
+        let Some(len) = isize::try_from(self.items.len())
+            .ok()
+            .filter(|len| *len > 0)
+        else {
+            return false;
+        };
+
+        let current = isize::try_from(self.selected).unwrap_or(0);
+        let next = usize::try_from((current + step).rem_euclid(len)).unwrap_or(0);
+
+        self.switch(next, ctx)
+    }
+
+    fn handle_key(&mut self, key : Keycode, modifiers : Modifiers, ctx : &mut EventCtx) -> bool {
+        let ctrl = Modifiers::CTRL;
+        let ctrl_shift = Modifiers::CTRL.union(Modifiers::SHIFT);
+
+        match (key, modifiers) {
+            (Keycode::Tab, mods) if mods == ctrl => self.cycle(1, ctx),
+            (Keycode::Tab, mods) if mods == ctrl_shift => self.cycle(-1, ctx),
+            (Keycode::PageDown, mods) if mods == ctrl => self.cycle(1, ctx),
+            (Keycode::PageUp, mods) if mods == ctrl => self.cycle(-1, ctx),
+            (Keycode::Right, Modifiers::NONE) if self.focused => self.cycle(1, ctx),
+            (Keycode::Left, Modifiers::NONE) if self.focused => self.cycle(-1, ctx),
+            (Keycode::Home, Modifiers::NONE) if self.focused => self.switch(0, ctx),
+            (Keycode::End, Modifiers::NONE) if self.focused => {
+                self.switch(self.items.len().saturating_sub(1), ctx)
+            },
+            (_, Modifiers::ALT) => {
+                let Some(chr) = char::from_u32(key.to_ll().0) else {
+                    return false;
+                };
+
+                let idx = self
+                    .items
+                    .iter()
+                    .position(|tab| tab.shortcut == Some(chr))
+                    .or_else(|| {
+                        chr.to_digit(10)
+                            .and_then(|digit| usize::try_from(digit).ok()?.checked_sub(1))
+                    });
+
+                idx.is_some_and(|idx| self.switch(idx, ctx))
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Widget for Tabs {
+    fn layout(&mut self, rect : CellRect, children : &mut [Node]) {
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+
+        self.titles.clear();
+        let mut x = rect.x;
+        for tab in &self.items {
+            let width = i32::try_from(tab.title.chars().count()).unwrap_or(0) + 2;
+            self.titles.push(CellRect::new(
+                x,
+                rect.y,
+                width.min(rect.right() - x).max(0),
+                1.min(rect.height),
+            ));
+            x += width + 1;
+        }
+
+        let panel = CellRect::new(rect.x, rect.y + 2, rect.width, (rect.height - 2).max(0));
+
+        for (idx, child) in children.iter_mut().enumerate() {
+            child.set_visible(idx == self.selected);
+            child.layout(panel);
+        }
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = self.palette.unwrap_or(ctx.palette);
+
+        let bar = CellRect::new(
+            ctx.rect.x,
+            ctx.rect.y,
+            ctx.rect.width,
+            ctx.rect.height.min(2),
+        );
+        console.fill(bar, Char437(b' '), palette);
+
+        for (idx, (tab, rect)) in self.items.iter().zip(&self.titles).enumerate() {
+            let tab_palette = if idx != self.selected {
+                palette
+            } else if ctx.focused {
+                palette.accent1()
+            } else {
+                palette.accent2()
+            };
+
+            console.fill(*rect, Char437(b' '), tab_palette);
+
+            let mut shortcut = tab.shortcut;
+            for (x, chr) in (rect.x + 1 .. rect.right() - 1).zip(tab.title.chars()) {
+                let glyph = chr.to_cp437().unwrap_or(Char437(b'?'));
+
+                if shortcut.is_some_and(|shortcut| shortcut.eq_ignore_ascii_case(&chr)) {
+                    shortcut = None;
+                    console.set(
+                        (x, rect.y),
+                        glyph,
+                        Palette {
+                            fg : palette.fg_accent2(),
+                            ..tab_palette
+                        },
+                    );
+                } else {
+                    console.set((x, rect.y), glyph, tab_palette);
+                }
+            }
+        }
+
+        if bar.height > 1 {
+            let line = self.style.chars().top;
+            for x in bar.x .. bar.right() {
+                console.set((x, bar.y + 1), line, palette);
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> EventResult {
+        let consumed = match event {
+            UiEvent::Key { key, modifiers, .. } => self.handle_key(*key, *modifiers, ctx),
+            UiEvent::Mouse(mouse) => {
+                match mouse.kind {
+                    MouseEventKind::Pressed {
+                        button: MouseButton::Left,
+                        ..
+                    } => {
+                        event
+                            .cell()
+                            .and_then(|cell| {
+                                self.titles.iter().position(|rect| rect.contains(cell))
+                            })
+                            .is_some_and(|idx| self.switch(idx, ctx))
+                    },
+                    _ => false,
+                }
+            },
+            UiEvent::Text(_) | UiEvent::Paste(_) => false,
+        };
+
+        if consumed {
+            EventResult::Consumed
+        } else {
+            EventResult::Ignored
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn focus_changed(&mut self, focused : bool) {
+        self.focused = focused;
+    }
+}
@@ -0,0 +1,431 @@
+use std::ops::Range;
+
+use sdl3::keyboard::Keycode;
+
+use crate::char::{CP437CharExt, Char437};
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::input::Modifiers;
+use crate::widget::{DrawCtx, EventCtx, EventResult, MessageKind, Node, UiEvent, Widget};
+
+/// Previously submitted lines, browsed with the up and down keys.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct History {
+    entries :  Vec<String>,
+    limit :    usize,
+    /// The entry being shown, `None` while editing a new line.
+    browsing : Option<usize>,
+    /// The new line, kept while browsing.
+    draft :    Vec<char>,
+}
+
+/// A single line of editable text, taking the text typed while it has focus.
+///
+/// Only characters that can be drawn in code page 437 are accepted. Pressing
+/// enter sends a [`MessageKind::Submitted`] holding the text.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TextInput {
+    text :    Vec<char>,
+    cursor :  usize,
+    /// The other end of the selection from the cursor.
+    anchor :  Option<usize>,
+    /// The first character shown.
+    scroll :  usize,
+    focused : bool,
+
+    history : Option<History>,
+
+    /// The most characters the text can hold.
+    pub max_length :      Option<usize>,
+    /// Shown while the text is empty.
+    pub placeholder :     String,
+    /// Whether the text is cleared after it is submitted.
+    pub clear_on_submit : bool,
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette :         Option<Palette>,
+}
+
+impl TextInput {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_text(mut self, text : &str) -> Self {
+        self.set_text(text);
+        self
+    }
+
+    #[must_use]
+    pub fn with_max_length(mut self, max_length : usize) -> Self {
+        self.max_length = Some(max_length);
+        self.set_text(&self.text());
+        self
+    }
+
+    #[must_use]
+    pub fn with_placeholder(mut self, placeholder : impl Into<String>) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    #[must_use]
+    pub const fn with_clear_on_submit(mut self, clear_on_submit : bool) -> Self {
+        self.clear_on_submit = clear_on_submit;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    /// Keeps up to `limit` submitted lines to browse with the up and down
+    /// keys, as in a command console.
+    #[must_use]
+    pub fn with_history(mut self, limit : usize) -> Self {
+        self.history = Some(History {
+            limit,
+            ..History::default()
+        });
+        self
+    }
+
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    /// Replaces the text, dropping characters that can't be shown and any past
+    /// the maximum length, and moves the cursor to the end.
+    pub fn set_text(&mut self, text : &str) {
+        self.text.clear();
+        self.cursor = 0;
+        self.anchor = None;
+        self.scroll = 0;
+        self.insert(text);
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text("");
+    }
+
+    /// The position of the cursor, in characters.
+    #[must_use]
+    pub const fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The selected characters, if any.
+    #[must_use]
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.anchor.filter(|anchor| *anchor != self.cursor)?;
+        Some(anchor.min(self.cursor) .. anchor.max(self.cursor))
+    }
+
+    #[must_use]
+    pub fn selected_text(&self) -> Option<String> {
+        Some(self.text[self.selection()?].iter().collect())
+    }
+
+    /// The submitted lines kept for browsing, oldest first.
+    #[must_use]
+    pub fn history(&self) -> &[String] {
+        self.history
+            .as_ref()
+            .map_or(&[], |history| &history.entries)
+    }
+
+    /// Replaces the selection with `text` at the cursor, keeping only the
+    /// characters that can be shown and that fit. Returns the number of
+    /// characters inserted.
+    pub fn insert(&mut self, text : &str) -> usize {
+        self.delete_selection();
+
+        let room = self
+            .max_length
+            .map_or(usize::MAX, |max| max.saturating_sub(self.text.len()));
+
+        let chars : Vec<char> = text
+            .chars()
+            .filter(|chr| !chr.is_control() && chr.is_cp437())
+            .take(room)
+            .collect();
+
+        let inserted = chars.len();
+        self.text.splice(self.cursor .. self.cursor, chars);
+        self.cursor += inserted;
+
+        inserted
+    }
+
+    /// Deletes the selected characters, returning whether there were any.
+    fn delete_selection(&mut self) -> bool {
+        let Some(selection) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+
+        self.cursor = selection.start;
+        self.text.drain(selection);
+        self.anchor = None;
+        true
+    }
+
+    /// Moves the cursor, extending the selection if `select` is set and
+    /// dropping it otherwise.
+    const fn move_to(&mut self, pos : usize, select : bool) {
+        if select {
+            if self.anchor.is_none() {
+                self.anchor = Some(self.cursor);
+            }
+        } else {
+            self.anchor = None;
+        }
+
+        self.cursor = pos;
+    }
+
+    /// Deletes from the cursor to `pos`, or the selection if there is one.
+    fn delete_to(&mut self, pos : usize) {
+        if self.delete_selection() {
+            return;
+        }
+
+        let range = self.cursor.min(pos) .. self.cursor.max(pos);
+        self.cursor = range.start;
+        self.text.drain(range);
+    }
+
+    /// The start of the word before the cursor.
+    fn word_start(&self) -> usize {
+        let mut pos = self.cursor;
+
+        while pos > 0 && !self.text[pos - 1].is_alphanumeric() {
+            pos -= 1;
+        }
+        while pos > 0 && self.text[pos - 1].is_alphanumeric() {
+            pos -= 1;
+        }
+
+        pos
+    }
+
+    /// The end of the word after the cursor.
+    fn word_end(&self) -> usize {
+        let mut pos = self.cursor;
+
+        while pos < self.text.len() && !self.text[pos].is_alphanumeric() {
+            pos += 1;
+        }
+        while pos < self.text.len() && self.text[pos].is_alphanumeric() {
+            pos += 1;
+        }
+
+        pos
+    }
+
+    /// Steps through the history, towards older lines if `back` is set.
+    /// Returns whether there was a line to step to.
+    fn browse(&mut self, back : bool) -> bool {
+        let Some(history) = &mut self.history else {
+            return false;
+        };
+
+        let next = match (history.browsing, back) {
+            (None, true) => history.entries.len().checked_sub(1),
+            (Some(idx), true) => idx.checked_sub(1),
+            (Some(idx), false) => Some(idx + 1),
+            (None, false) => return false,
+        };
+
+        let Some(next) = next else {
+            return false;
+        };
+
+        if history.browsing.is_none() {
+            history.draft.clone_from(&self.text);
+        }
+
+        history.browsing = (next < history.entries.len()).then_some(next);
+
+        let text = history
+            .entries
+            .get(next)
+            .map_or_else(|| history.draft.clone(), |entry| entry.chars().collect());
+
+        self.text = text;
+        self.cursor = self.text.len();
+        self.anchor = None;
+        true
+    }
+
+    /// Sends the text to the host, adding it to the history.
+    fn submit(&mut self, ctx : &mut EventCtx) {
+        let text = self.text();
+
+        if let Some(history) = &mut self.history {
+            history.browsing = None;
+            history.draft.clear();
+
+            if !text.is_empty() && history.entries.last() != Some(&text) {
+                history.entries.push(text.clone());
+            }
+
+            let excess = history.entries.len().saturating_sub(history.limit);
+            history.entries.drain(.. excess);
+        }
+
+        ctx.emit(MessageKind::Submitted(text));
+
+        if self.clear_on_submit {
+            self.clear();
+        }
+    }
+
+    /// Scrolls so that the cursor is inside a field `width` cells wide.
+    fn scroll_to_cursor(&mut self, width : i32) {
+        let width = usize::try_from(width).unwrap_or(0).max(1);
+
+        if self.cursor < self.scroll {
+            self.scroll = self.cursor;
+        } else if self.cursor >= self.scroll + width {
+            self.scroll = self.cursor + 1 - width;
+        }
+
+        // Don't leave empty cells at the end while there is text to show
+        self.scroll = self.scroll.min((self.text.len() + 1).saturating_sub(width));
+    }
+
+    fn handle_key(&mut self, key : Keycode, modifiers : Modifiers, ctx : &mut EventCtx) -> bool {
+        let select = modifiers.contains(Modifiers::SHIFT);
+        let word = modifiers.contains(Modifiers::CTRL);
+
+        match key {
+            Keycode::Left if word => self.move_to(self.word_start(), select),
+            Keycode::Right if word => self.move_to(self.word_end(), select),
+            Keycode::Left => {
+                match self.selection() {
+                    Some(selection) if !select => self.move_to(selection.start, false),
+                    _ => self.move_to(self.cursor.saturating_sub(1), select),
+                }
+            },
+            Keycode::Right => {
+                match self.selection() {
+                    Some(selection) if !select => self.move_to(selection.end, false),
+                    _ => self.move_to((self.cursor + 1).min(self.text.len()), select),
+                }
+            },
+            Keycode::Home => self.move_to(0, select),
+            Keycode::End => self.move_to(self.text.len(), select),
+            Keycode::A if word => {
+                self.anchor = Some(0);
+                self.cursor = self.text.len();
+            },
+            Keycode::Backspace if word => self.delete_to(self.word_start()),
+            Keycode::Delete if word => self.delete_to(self.word_end()),
+            Keycode::Backspace => self.delete_to(self.cursor.saturating_sub(1)),
+            Keycode::Delete => self.delete_to((self.cursor + 1).min(self.text.len())),
+            Keycode::Up => return self.browse(true),
+            Keycode::Down => return self.browse(false),
+            Keycode::Return | Keycode::KpEnter => self.submit(ctx),
+            // Let focus move and the host see escape
+            Keycode::Tab | Keycode::Escape => return false,
+            // Swallow the presses that also arrive as text, so they don't
+            // trigger actions bound to them
+            _ => {
+                return !modifiers.contains(Modifiers::CTRL) && !modifiers.contains(Modifiers::ALT);
+            },
+        }
+
+        true
+    }
+}
+
+impl Widget for TextInput {
+    fn measure(&self, (width, height) : (i32, i32), _children : &[Node]) -> (i32, i32) {
+        (width, height.min(1))
+    }
+
+    fn layout(&mut self, rect : CellRect, _children : &mut [Node]) {
+        self.scroll_to_cursor(rect.width);
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = ctx.highlight(self.palette.unwrap_or(ctx.palette));
+        let inverted = Palette::simple(palette.bg, palette.fg);
+
+        let row = CellRect::new(
+            ctx.rect.x,
+            ctx.rect.y,
+            ctx.rect.width,
+            ctx.rect.height.min(1),
+        );
+        console.fill(row, Char437(b' '), palette);
+
+        if self.text.is_empty() && !ctx.focused {
+            let placeholder : String = self
+                .placeholder
+                .chars()
+                .take(usize::try_from(row.width).unwrap_or(0))
+                .collect();
+
+            console.print(
+                (row.x, row.y),
+                &placeholder,
+                Palette {
+                    fg : palette.fg_accent2(),
+                    ..palette
+                },
+            );
+            return;
+        }
+
+        let selection = self.selection().unwrap_or_default();
+
+        for (x, idx) in (row.x .. row.right()).zip(self.scroll ..) {
+            let selected = selection.contains(&idx) || (ctx.focused && idx == self.cursor);
+            let glyph = self
+                .text
+                .get(idx)
+                .and_then(CP437CharExt::to_cp437)
+                .unwrap_or(Char437(b' '));
+
+            console.set((x, row.y), glyph, if selected { inverted } else { palette });
+        }
+    }
+
+    fn handle_event(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> EventResult {
+        let consumed = match event {
+            // Only the focused input takes keys and text
+            _ if !self.focused => false,
+            UiEvent::Key { key, modifiers, .. } => self.handle_key(*key, *modifiers, ctx),
+            UiEvent::Text(text) | UiEvent::Paste(text) => {
+                self.insert(text);
+                true
+            },
+            UiEvent::Mouse(_) => false,
+        };
+
+        if !consumed {
+            return EventResult::Ignored;
+        }
+
+        self.scroll_to_cursor(ctx.rect.width);
+        EventResult::Consumed
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+
+    fn focus_changed(&mut self, focused : bool) {
+        self.focused = focused;
+    }
+
+    fn wants_text_input(&self) -> bool {
+        true
+    }
+}
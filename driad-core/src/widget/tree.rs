@@ -0,0 +1,492 @@
+use sdl3::keyboard::Keycode;
+use sdl3::mouse::MouseButton;
+
+use crate::char::{CP437CharExt, Char437};
+use crate::color::Palette;
+use crate::console::{CellRect, Console};
+use crate::input::Modifiers;
+use crate::mouse::MouseEventKind;
+use crate::widget::scrollbar::Scrollbar;
+use crate::widget::{DrawCtx, EventCtx, EventResult, MessageKind, Node, UiEvent, Widget};
+
+const VERTICAL : Char437 = Char437(0xB3);
+const BRANCH : Char437 = Char437(0xC3);
+const LAST_BRANCH : Char437 = Char437(0xC0);
+const HORIZONTAL : Char437 = Char437(0xC4);
+const COLLAPSED : Char437 = Char437(0x10);
+const EXPANDED : Char437 = Char437(0x1F);
+
+/// An entry in a [`Tree`], along with the entries nested under it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeItem {
+    pub label :    String,
+    pub children : Vec<Self>,
+    /// Whether the children are shown.
+    pub expanded : bool,
+    /// Drawn with the palette of the tree when `None`.
+    pub palette :  Option<Palette>,
+}
+
+impl TreeItem {
+    #[must_use]
+    pub fn new(label : impl Into<String>) -> Self {
+        Self {
+            label :    label.into(),
+            children : Vec::new(),
+            expanded : false,
+            palette :  None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_child(mut self, child : impl Into<Self>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_children(mut self, children : impl IntoIterator<Item = impl Into<Self>>) -> Self {
+        self.children.extend(children.into_iter().map(Into::into));
+        self
+    }
+
+    #[must_use]
+    pub const fn with_expanded(mut self, expanded : bool) -> Self {
+        self.expanded = expanded;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+}
+
+impl<T : Into<String>> From<T> for TreeItem {
+    fn from(label : T) -> Self {
+        Self::new(label)
+    }
+}
+
+/// A shown row of a [`Tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Row {
+    path :   Vec<usize>,
+    /// The connectors and marker before the label.
+    prefix : Vec<Char437>,
+}
+
+/// A scrollable tree of collapsible items, one per row, drawn with line
+/// connectors (`├─ └─ │`), such as a settings screen or a skill tree.
+///
+/// Items are addressed by their path, the index of each item from the roots
+/// down. The up and down, page and home/end keys move the highlight, right
+/// expands the highlighted item or moves into it, left collapses it or moves
+/// to its parent, and space toggles it. Enter, or clicking an item without
+/// children, sends a [`MessageKind::TreeActivated`], while clicking an item
+/// with children toggles it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Tree {
+    items :       Vec<TreeItem>,
+    highlighted : Option<Vec<usize>>,
+    /// The first row shown.
+    scroll :      usize,
+    /// The number of rows the tree was laid out with.
+    height :      usize,
+
+    /// Drawn with the palette of the [`Ui`](super::Ui) when `None`.
+    pub palette : Option<Palette>,
+}
+
+impl Tree {
+    #[must_use]
+    pub fn new(items : impl IntoIterator<Item = impl Into<TreeItem>>) -> Self {
+        let mut tree = Self::default();
+        tree.set_items(items);
+        tree
+    }
+
+    #[must_use]
+    pub const fn with_palette(mut self, palette : Palette) -> Self {
+        self.palette = Some(palette);
+        self
+    }
+
+    #[must_use]
+    pub fn items(&self) -> &[TreeItem] {
+        &self.items
+    }
+
+    /// Replaces the items, highlighting the first.
+    pub fn set_items(&mut self, items : impl IntoIterator<Item = impl Into<TreeItem>>) {
+        self.items = items.into_iter().map(Into::into).collect();
+        self.scroll = 0;
+        self.highlighted = (!self.items.is_empty()).then(|| vec![0]);
+    }
+
+    #[must_use]
+    pub fn item(&self, path : &[usize]) -> Option<&TreeItem> {
+        let (first, rest) = path.split_first()?;
+
+        rest.iter()
+            .try_fold(self.items.get(*first)?, |item, idx| item.children.get(*idx))
+    }
+
+    pub fn item_mut(&mut self, path : &[usize]) -> Option<&mut TreeItem> {
+        let (first, rest) = path.split_first()?;
+
+        rest.iter()
+            .try_fold(self.items.get_mut(*first)?, |item, idx| {
+                item.children.get_mut(*idx)
+            })
+    }
+
+    /// The path of the item under the highlight.
+    #[must_use]
+    pub fn highlighted(&self) -> Option<&[usize]> {
+        self.highlighted.as_deref()
+    }
+
+    /// Moves the highlight to an item, expanding its parents so it is shown.
+    pub fn highlight(&mut self, path : &[usize]) {
+        if self.item(path).is_none() {
+            return;
+        }
+
+        for len in 1 .. path.len() {
+            if let Some(parent) = self.item_mut(&path[.. len]) {
+                parent.expanded = true;
+            }
+        }
+
+        self.highlighted = Some(path.to_vec());
+        self.scroll_to_highlight();
+    }
+
+    /// Expands or collapses an item, moving the highlight out of it if it was
+    /// inside.
+    pub fn set_expanded(&mut self, path : &[usize], expanded : bool) {
+        let Some(item) = self.item_mut(path) else {
+            return;
+        };
+        item.expanded = expanded;
+
+        if !expanded
+            && self.highlighted.as_ref().is_some_and(|highlighted| {
+                highlighted.len() > path.len() && highlighted.starts_with(path)
+            })
+        {
+            self.highlighted = Some(path.to_vec());
+        }
+
+        self.scroll_by(0);
+        self.scroll_to_highlight();
+    }
+
+    /// The rows shown, in order.
+    fn rows(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut path = Vec::new();
+        let mut prefix = Vec::new();
+
+        collect_rows(&self.items, &mut path, &mut prefix, &mut rows);
+        rows
+    }
+
+    fn highlighted_row(&self, rows : &[Row]) -> Option<usize> {
+        let highlighted = self.highlighted.as_ref()?;
+        rows.iter().position(|row| &row.path == highlighted)
+    }
+
+    const fn scrollbar(&self, rows : usize) -> Scrollbar {
+        Scrollbar::new(self.scroll, self.height, rows)
+    }
+
+    /// Scrolls the view without moving the highlight.
+    fn scroll_by(&mut self, lines : isize) {
+        let max = self.rows().len().saturating_sub(self.height);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max);
+    }
+
+    fn scroll_to_highlight(&mut self) {
+        let Some(highlighted) = self.highlighted_row(&self.rows()) else {
+            return;
+        };
+
+        let height = self.height.max(1);
+
+        if highlighted < self.scroll {
+            self.scroll = highlighted;
+        } else if highlighted >= self.scroll + height {
+            self.scroll = highlighted + 1 - height;
+        }
+    }
+
+    /// Moves the highlight by `step` rows, stopping at the ends.
+    fn step(&mut self, step : isize) {
+        let rows = self.rows();
+
+        let Some(last) = rows.len().checked_sub(1) else {
+            return;
+        };
+
+        let next = match self.highlighted_row(&rows) {
+            Some(current) => current.saturating_add_signed(step).min(last),
+            None if step < 0 => last,
+            None => 0,
+        };
+
+        self.highlighted = Some(rows[next].path.clone());
+        self.scroll_to_highlight();
+    }
+
+    /// Expands or collapses an item, returning whether it has children to
+    /// show or hide.
+    fn toggle(&mut self, path : &[usize]) -> bool {
+        let Some(item) = self.item(path).filter(|item| !item.children.is_empty()) else {
+            return false;
+        };
+
+        self.set_expanded(path, !item.expanded);
+        true
+    }
+
+    /// Goes into the highlighted item, expanding it first if needed.
+    fn enter(&mut self) -> bool {
+        let Some(path) = self.highlighted.clone() else {
+            return false;
+        };
+        let Some(item) = self.item(&path).filter(|item| !item.children.is_empty()) else {
+            return false;
+        };
+
+        if item.expanded {
+            self.highlight(&[path.as_slice(), &[0]].concat());
+        } else {
+            self.set_expanded(&path, true);
+        }
+
+        true
+    }
+
+    /// Goes out of the highlighted item, collapsing it first if needed.
+    fn leave(&mut self) -> bool {
+        let Some(path) = self.highlighted.clone() else {
+            return false;
+        };
+
+        if self
+            .item(&path)
+            .is_some_and(|item| item.expanded && !item.children.is_empty())
+        {
+            self.set_expanded(&path, false);
+            return true;
+        }
+
+        match path.split_last() {
+            Some((_, parent)) if !parent.is_empty() => {
+                self.highlight(parent);
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn handle_key(&mut self, key : Keycode, modifiers : Modifiers, ctx : &mut EventCtx) -> bool {
+        if modifiers != Modifiers::NONE {
+            return false;
+        }
+
+        let page = isize::try_from(self.height.max(1)).unwrap_or(1);
+
+        match key {
+            Keycode::Up => self.step(-1),
+            Keycode::Down => self.step(1),
+            Keycode::PageUp => self.step(-page),
+            Keycode::PageDown => self.step(page),
+            Keycode::Home => self.step(isize::MIN),
+            Keycode::End => self.step(isize::MAX),
+            Keycode::Right => return self.enter(),
+            Keycode::Left => return self.leave(),
+            Keycode::Space => {
+                let Some(path) = self.highlighted.clone() else {
+                    return false;
+                };
+                return self.toggle(&path);
+            },
+            Keycode::Return | Keycode::KpEnter => {
+                let Some(path) = self.highlighted.clone() else {
+                    return false;
+                };
+                ctx.emit(MessageKind::TreeActivated(path));
+            },
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn handle_mouse(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> bool {
+        let UiEvent::Mouse(mouse) = event else {
+            return false;
+        };
+        let Some((x, y)) = event.cell() else {
+            return false;
+        };
+
+        let rows = self.rows();
+        let row = self.scroll + usize::try_from(y - ctx.rect.y).unwrap_or(0);
+        let scrollbar = self.scrollbar(rows.len());
+
+        match mouse.kind {
+            MouseEventKind::Wheel { y: wheel, .. } => {
+                self.scroll_by(if wheel > 0.0 { -3 } else { 3 });
+                true
+            },
+            MouseEventKind::Pressed {
+                button: MouseButton::Left,
+                ..
+            } if scrollbar.is_needed() && x == ctx.rect.right() - 1 => {
+                self.scroll = scrollbar.offset_at(y - ctx.rect.y, ctx.rect.height);
+                true
+            },
+            MouseEventKind::Pressed {
+                button: MouseButton::Left,
+                ..
+            } => {
+                let Some(row) = rows.get(row) else {
+                    return false;
+                };
+
+                self.highlighted = Some(row.path.clone());
+
+                if !self.toggle(&row.path) {
+                    ctx.emit(MessageKind::TreeActivated(row.path.clone()));
+                }
+
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Adds the rows of `items` and their expanded children, `path` and `prefix`
+/// being those of their parent.
+fn collect_rows(
+    items : &[TreeItem],
+    path : &mut Vec<usize>,
+    prefix : &mut Vec<Char437>,
+    rows : &mut Vec<Row>,
+) {
+    let nested = !path.is_empty();
+
+    for (idx, item) in items.iter().enumerate() {
+        let last = idx + 1 == items.len();
+
+        path.push(idx);
+
+        let mut row_prefix = prefix.clone();
+        if nested {
+            row_prefix.extend([if last { LAST_BRANCH } else { BRANCH }, HORIZONTAL]);
+        }
+        row_prefix.push(match (item.children.is_empty(), item.expanded) {
+            (false, true) => EXPANDED,
+            (false, false) => COLLAPSED,
+            (true, _) if nested => HORIZONTAL,
+            (true, _) => Char437(b' '),
+        });
+
+        rows.push(Row {
+            path :   path.clone(),
+            prefix : row_prefix,
+        });
+
+        if item.expanded {
+            // The children line up under this item's marker
+            let len = prefix.len();
+            if nested {
+                prefix.extend([if last { Char437(b' ') } else { VERTICAL }, Char437(b' ')]);
+            }
+
+            collect_rows(&item.children, path, prefix, rows);
+            prefix.truncate(len);
+        }
+
+        path.pop();
+    }
+}
+
+impl Widget for Tree {
+    fn layout(&mut self, rect : CellRect, _children : &mut [Node]) {
+        self.height = usize::try_from(rect.height).unwrap_or(0);
+        self.scroll_by(0);
+        self.scroll_to_highlight();
+    }
+
+    fn draw(&self, console : &mut Console, ctx : &DrawCtx) {
+        let palette = self.palette.unwrap_or(ctx.palette);
+        let rows = self.rows();
+        let scrollbar = self.scrollbar(rows.len());
+        let width = ctx.rect.width - i32::from(scrollbar.is_needed());
+
+        console.fill(ctx.rect, Char437(b' '), palette);
+
+        for (y, row) in (ctx.rect.y .. ctx.rect.bottom()).zip(rows.iter().skip(self.scroll)) {
+            let Some(item) = self.item(&row.path) else {
+                continue;
+            };
+
+            let mut item_palette = item.palette.unwrap_or(palette);
+            if self.highlighted.as_ref() == Some(&row.path) {
+                item_palette = if ctx.focused {
+                    item_palette.accent1()
+                } else {
+                    item_palette.accent2()
+                };
+            }
+
+            let label = item
+                .label
+                .chars()
+                .map(|chr| chr.to_cp437().unwrap_or(Char437(b'?')));
+            let glyphs = row
+                .prefix
+                .iter()
+                .map(|glyph| (*glyph, palette))
+                .chain([(Char437(b' '), item_palette)])
+                .chain(label.map(|glyph| (glyph, item_palette)));
+
+            for (x, (glyph, glyph_palette)) in (ctx.rect.x .. ctx.rect.x + width).zip(glyphs) {
+                console.set((x, y), glyph, glyph_palette);
+            }
+        }
+
+        if scrollbar.is_needed() {
+            scrollbar.draw(
+                console,
+                CellRect::new(ctx.rect.right() - 1, ctx.rect.y, 1, ctx.rect.height),
+                palette,
+            );
+        }
+    }
+
+    fn handle_event(&mut self, event : &UiEvent, ctx : &mut EventCtx) -> EventResult {
+        let consumed = match event {
+            UiEvent::Key { key, modifiers, .. } => self.handle_key(*key, *modifiers, ctx),
+            UiEvent::Mouse(_) => self.handle_mouse(event, ctx),
+            UiEvent::Text(_) | UiEvent::Paste(_) => false,
+        };
+
+        if consumed {
+            EventResult::Consumed
+        } else {
+            EventResult::Ignored
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,14 @@
+-- Draws a line of text for the headless snapshot test
+local function draw_pass()
+    return {
+        kind = "str",
+        x = 1,
+        y = 2,
+        text = "from lua",
+        fg = { r = 0, g = 255, b = 0 },
+    }
+end
+
+return {
+    draw_pass = draw_pass,
+}
@@ -0,0 +1,202 @@
+//! Renders consoles without a window and checks them against the images in
+//! `tests/snapshots`, which are written by running the tests with
+//! `DRIAD_UPDATE_SNAPSHOTS` set.
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+use driad_core::color::{Color, Palette};
+use driad_core::console::Console;
+use driad_core::draw::{DrawCommand, DrawPass};
+use driad_core::font::Font;
+use driad_core::snapshot::{self, SnapshotError, UPDATE_SNAPSHOTS_VAR};
+use driad_core::{Driad, WindowProperties};
+use image::{GenericImageView, Rgb, RgbImage};
+use sdl3::pixels::PixelFormat;
+use sdl3::surface::Surface;
+
+const PUT_STR_COLOR : Color = Color::new(0, 255, 255);
+const LUA_COLOR : Color = Color::new(0, 255, 0);
+
+fn font() -> Result<Font, Box<dyn Error>> {
+    Ok(Font::new(
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../assets/Alloy_curses_12x12.png"
+        ),
+        Palette::simple(Color::new(255, 255, 255), Color::new(255, 0, 255)),
+    )?)
+}
+
+fn console() -> Console {
+    let mut console = Console::new(12, 3);
+    console.print(
+        (1, 1),
+        "Hello, @!",
+        Palette::simple(Color::new(255, 200, 0), Color::new(0, 0, 128)),
+    );
+    console
+}
+
+/// The console the headless test should end up showing, with the text drawn
+/// by its draw pass and by the lua plugin.
+fn headless_console() -> Console {
+    let mut console = console();
+    console.print((0, 0), "put_str", PUT_STR_COLOR);
+    console.print((1, 2), "from lua", LUA_COLOR);
+    console
+}
+
+/// Draws a console onto a software canvas and reads it back.
+fn render(font : &Font, console : &Console) -> Result<RgbImage, Box<dyn Error>> {
+    let width = u32::try_from(console.width())? * font.glyph_width;
+    let height = u32::try_from(console.height())? * font.glyph_height;
+
+    let mut canvas = Surface::new(width, height, PixelFormat::RGBA32)?.into_canvas()?;
+    canvas.set_draw_color(Color::new(0, 0, 0));
+    canvas.clear();
+
+    font.flush(&mut canvas, console, (0, 0))?;
+
+    Ok(snapshot::read_canvas(&canvas)?)
+}
+
+/// Draws a console pixel by pixel from the font's atlas, as the renderer
+/// should.
+fn reference(font : &Font, console : &Console) -> Result<RgbImage, Box<dyn Error>> {
+    let mut image = RgbImage::new(
+        u32::try_from(console.width())? * font.glyph_width,
+        u32::try_from(console.height())? * font.glyph_height,
+    );
+
+    for ((x, y), cell) in console.iter() {
+        let (x, y) = (
+            u32::try_from(x)? * font.glyph_width,
+            u32::try_from(y)? * font.glyph_height,
+        );
+
+        for (glyph_x, glyph_y, pixel) in font.lookup_char(cell.glyph).pixels() {
+            let color = if pixel == Rgb([255, 255, 255]) {
+                cell.fg
+            } else {
+                cell.bg.unwrap_or(Color::new(0, 0, 0))
+            };
+
+            image.put_pixel(x + glyph_x, y + glyph_y, Rgb(color.into()));
+        }
+    }
+
+    Ok(image)
+}
+
+/// The committed image a snapshot is compared against.
+fn golden(name : &str) -> PathBuf {
+    PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots"))
+        .join(format!("{name}.png"))
+}
+
+/// A path to write to for the duration of a test.
+fn temp_path(name : &str) -> PathBuf {
+    env::temp_dir().join(format!("driad-{}-{name}.png", process::id()))
+}
+
+#[test]
+fn goldens_match_reference() -> Result<(), Box<dyn Error>> {
+    let font = font()?;
+
+    for (name, console) in [("console", console()), ("headless", headless_console())] {
+        let golden = image::open(golden(name))?.into_rgb8();
+
+        assert_eq!(
+            snapshot::count_differences(&reference(&font, &console)?, &golden, 1)?,
+            0,
+            "{name}.png doesn't match the reference"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn render_matches_golden() -> Result<(), Box<dyn Error>> {
+    let font = font()?;
+    let console = console();
+    let rendered = render(&font, &console)?;
+
+    assert_eq!(
+        snapshot::count_differences(&rendered, &reference(&font, &console)?, 1)?,
+        0
+    );
+    snapshot::assert_snapshot(&rendered, golden("console"), 1)?;
+
+    Ok(())
+}
+
+#[test]
+fn headless_frame_matches_golden() -> Result<(), Box<dyn Error>> {
+    let properties = WindowProperties {
+        width : 12,
+        height : 3,
+        centered : false,
+        headless : true,
+        ..WindowProperties::default()
+    };
+    let plugin = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/plugins/snapshot");
+
+    let mut driad = Driad::new(&properties, font()?, &[plugin])?;
+    driad.init_plugins()?;
+
+    driad.base_mut().console = console();
+    driad.submit(DrawPass::from_iter([DrawCommand::PutStr {
+        pos :     (0, 0),
+        text :    "put_str".to_owned(),
+        palette : PUT_STR_COLOR.into(),
+    }]));
+    driad.submit_plugin_draws();
+    driad.render()?;
+
+    let path = temp_path("headless");
+    driad.screenshot(&path)?;
+    let frame = image::open(&path)?.into_rgb8();
+    fs::remove_file(&path)?;
+
+    let expected = reference(&driad.base().font, &headless_console())?;
+    assert_eq!(snapshot::count_differences(&frame, &expected, 1)?, 0);
+    snapshot::assert_snapshot(&frame, golden("headless"), 1)?;
+
+    Ok(())
+}
+
+#[test]
+fn changed_render_is_a_mismatch() -> Result<(), Box<dyn Error>> {
+    if env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        return Ok(());
+    }
+
+    let font = font()?;
+
+    let mut changed = console();
+    changed.print((1, 1), "J", Color::new(255, 200, 0));
+
+    let result = snapshot::assert_snapshot(&render(&font, &changed)?, golden("console"), 1);
+    assert!(matches!(result, Err(SnapshotError::Mismatch { .. })));
+
+    Ok(())
+}
+
+#[test]
+fn missing_golden_is_an_error() -> Result<(), Box<dyn Error>> {
+    if env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        return Ok(());
+    }
+
+    let font = font()?;
+    let path = temp_path("missing");
+
+    let result = snapshot::assert_snapshot(&render(&font, &console())?, &path, 1);
+    assert!(matches!(result, Err(SnapshotError::MissingGolden(_))));
+    assert!(!path.exists());
+
+    Ok(())
+}
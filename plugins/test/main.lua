@@ -5,9 +5,19 @@ end
 
 local function draw_pass()
    return {
-    x = 15,
-    y = 5,
-    glyph = 'Z',
+    {
+        x = 15,
+        y = 5,
+        glyph = 'Z',
+    },
+    {
+        kind = "str",
+        x = 15,
+        y = 6,
+        text = "Drawn from lua",
+        fg = { r = 255, g = 0, b = 255 },
+        z = 1,
+    },
    }
 end
 
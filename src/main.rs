@@ -1,16 +1,119 @@
 use std::error::Error;
+use std::ops::ControlFlow;
 use std::time::Duration;
 
-use ::std::thread::sleep;
+use driad_core::app::App;
+use driad_core::char::Char437;
 use driad_core::color::{Color, Palette};
 use driad_core::font::Font;
-use driad_core::plugin::PluginApi;
+use driad_core::input::Keybindings;
+use driad_core::layout::{Constraint, Layout};
+use driad_core::mouse::{MouseEvent, MouseEventKind};
+use driad_core::widget::dialog::Dialog;
+use driad_core::widget::gauge::Gauge;
+use driad_core::widget::label::Label;
+use driad_core::widget::message_log::{LogEntry, MessageLog};
+use driad_core::widget::panel::Panel;
+use driad_core::widget::split::Split;
+use driad_core::widget::tabs::{Tab, Tabs};
+use driad_core::widget::text_input::TextInput;
+use driad_core::widget::tree::{Tree, TreeItem};
+use driad_core::widget::{MessageKind, Node, Stack, WidgetId};
 use driad_core::{Driad, WindowProperties};
-use log::{LevelFilter, warn};
-use sdl3::event::Event;
-use sdl3::keyboard::Keycode;
+use log::LevelFilter;
+use sdl3::mouse::MouseButton;
 use simplelog::{Config, SimpleLogger};
 
+struct Game {
+    pos :         (i32, i32),
+    turn :        u64,
+    log :         WidgetId,
+    quit_dialog : Option<WidgetId>,
+}
+
+impl Game {
+    fn log(&self, driad : &mut Driad, entry : LogEntry) {
+        if let Some(log) = driad.ui.widget_mut::<MessageLog>(self.log) {
+            log.push(entry.with_turn(self.turn));
+        }
+    }
+}
+
+impl App for Game {
+    fn update(&mut self, driad : &mut Driad, _dt : Duration) -> ControlFlow<()> {
+        for message in driad.ui.take_messages() {
+            if Some(message.source) == self.quit_dialog {
+                if message.kind == MessageKind::DialogClosed(0) {
+                    return ControlFlow::Break(());
+                }
+                self.quit_dialog = None;
+            } else if let MessageKind::Submitted(name) = message.kind
+                && !name.is_empty()
+            {
+                self.log(driad, LogEntry::new(format!("Welcome, {name}.")));
+            }
+        }
+
+        if driad.input.pressed("quit") && self.quit_dialog.is_none() {
+            self.quit_dialog = Some(
+                driad
+                    .ui
+                    .open_dialog(Dialog::confirm("Quit", "Leave the dungeon?")),
+            );
+        }
+
+        let input = &driad.input;
+        let pos = self.pos;
+
+        if input.pressed("move_north") {
+            self.pos.1 -= 1;
+        }
+        if input.pressed("move_south") {
+            self.pos.1 += 1;
+        }
+        if input.pressed("move_west") {
+            self.pos.0 -= 1;
+        }
+        if input.pressed("move_east") {
+            self.pos.0 += 1;
+        }
+
+        if self.pos != pos {
+            self.turn += 1;
+
+            if self.turn.is_multiple_of(10) {
+                self.log(
+                    driad,
+                    LogEntry::new("You hear a noise.").with_category("sound"),
+                );
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn handle_mouse(&mut self, _driad : &mut Driad, event : &MouseEvent) -> ControlFlow<()> {
+        if let MouseEventKind::Pressed {
+            button: MouseButton::Left,
+            ..
+        } = event.kind
+            && let Some(cell) = event.cell(0)
+        {
+            self.pos = cell;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn draw(&mut self, driad : &mut Driad, _alpha : f64) {
+        let console = &mut driad.base_mut().console;
+        console.clear();
+
+        console.print((2, 2), "Hello World!", Color::new(255, 255, 255));
+        console.set(self.pos, Char437::from(b'@'), Color::new(255, 255, 0));
+    }
+}
+
 /// Main Entrypoint to the program.
 fn main() -> Result<(), Box<dyn Error>> {
     SimpleLogger::init(LevelFilter::Trace, Config::default())?;
@@ -32,77 +135,89 @@ fn main() -> Result<(), Box<dyn Error>> {
     )?;
 
     let mut driad = Driad::new(
-        &WindowProperties::default(),
+        &WindowProperties {
+            resizable : true,
+            ..WindowProperties::default()
+        },
         font,
         &Vec::<String>::new(),
         // vec!["plugins/test", "plugins/other"],
     )?;
 
     driad.init_plugins()?;
+    driad.input.bindings = Keybindings::load("assets/keybindings.toml")?;
 
     driad.canvas.set_draw_color(Color::new(0, 255, 255));
     driad.canvas.clear();
     driad.canvas.present();
 
-    let mut pos_x = 12;
-    let mut pos_y = 12;
-
-    'running: loop {
-        driad.canvas.set_draw_color(Color::new(0, 0, 0));
-        driad.canvas.clear();
-
-        driad.font.put_str(
-            &mut driad.canvas,
-            "Hello World!",
-            (2, 2),
-            Color::new(255, 255, 255),
-        )?;
-
-        driad.font.put_char(
-            &mut driad.canvas,
-            '@',
-            (pos_x, pos_y),
-            Color::new(255, 255, 0),
-        )?;
-
-        for event in driad.event_pump.poll_iter() {
-            if let Event::KeyDown {
-                keycode: Some(key), ..
-            } = event
-            {
-                match key {
-                    Keycode::Escape => break 'running,
-                    Keycode::Up => pos_y -= 1,
-                    Keycode::Down => pos_y += 1,
-                    Keycode::Right => pos_x += 1,
-                    Keycode::Left => pos_x -= 1,
-                    _ => (),
-                }
-            }
-        }
+    let sidebar = Node::new(Split::new(Layout::vertical([
+        Constraint::Length(10),
+        Constraint::Length(1),
+        Constraint::Length(1),
+        Constraint::Fill(1),
+    ])))
+    .with_child(
+        Node::new(Panel::new().with_title("Driad")).with_child(
+            Node::new(Tabs::new([
+                Tab::new("Help").with_shortcut('h'),
+                Tab::new("Pack").with_shortcut('p'),
+            ]))
+            .with_child(Node::new(Label::new(
+                "Move with the arrow keys or click a cell.",
+            )))
+            .with_child(Node::new(Tree::new([
+                TreeItem::new("Weapons")
+                    .with_children(["Dagger", "Sling"])
+                    .with_expanded(true),
+                TreeItem::new("Potions").with_children(["Healing", "Speed"]),
+            ]))),
+        ),
+    )
+    .with_child(Node::new(
+        TextInput::new()
+            .with_placeholder("Name your hero")
+            .with_max_length(16),
+    ))
+    .with_child(Node::new(
+        Gauge::new(17.0, 30.0)
+            .with_label("HP")
+            .with_show_value(true)
+            .with_palette(Palette::simple(
+                Color::new(0, 160, 0),
+                Color::new(40, 40, 40),
+            ))
+            .with_threshold(
+                0.5,
+                Palette::simple(Color::new(200, 160, 0), Color::new(40, 40, 40)),
+            )
+            .with_threshold(
+                0.25,
+                Palette::simple(Color::new(200, 0, 0), Color::new(40, 40, 40)),
+            ),
+    ));
 
-        for plugin in &driad.plugins {
-            plugin.draw_pass().inspect(|a| {
-                match a {
-                    Ok(draw) => {
-                        match driad.font.put_char(
-                            &mut driad.canvas,
-                            draw.glyph,
-                            (draw.x, draw.y),
-                            Color::new(255, 255, 255),
-                        ) {
-                            Ok(()) => (),
-                            Err(err) => warn!("{err}"),
-                        }
-                    },
-                    Err(err) => warn!("{err}"),
-                }
-            });
-        }
+    let mut log = MessageLog::new();
+    log.push("Welcome to the dungeon.");
+    let log = Node::new(log);
+    let log_id = log.id();
+    let sidebar = sidebar.with_child(log);
 
-        driad.canvas.present();
-        sleep(Duration::new(0, 1_000_000_000u32 / 60));
-    }
+    driad.ui.add(
+        Node::new(Split::new(Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(20),
+        ])))
+        .with_child(Node::new(Stack))
+        .with_child(sidebar),
+    );
+
+    driad.run(&mut Game {
+        pos :         (12, 12),
+        turn :        0,
+        log :         log_id,
+        quit_dialog : None,
+    })?;
 
     Ok(())
 }